@@ -0,0 +1,190 @@
+use std::fmt;
+
+use crate::{
+    event::{Event, Outcome},
+    Game,
+};
+
+/// How long an effect lasts, counted in played actions of any player or in rounds
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Duration {
+    Actions(u64),
+    Rounds(u64),
+}
+
+impl Duration {
+    /// Parses `N:actions` or `N:rounds`
+    pub fn parse(text: &str) -> Option<Self> {
+        let (amount, unit) = text.split_once(':')?;
+        let amount = amount.parse().ok().filter(|&amount| amount > 0)?;
+        match unit {
+            "actions" => Some(Duration::Actions(amount)),
+            "rounds" => Some(Duration::Rounds(amount)),
+            _ => None,
+        }
+    }
+}
+
+/// Durations of timed effects, see `Rules::effects`. Effects without a duration last forever
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Effects {
+    // How long a player stays invisible
+    pub invisibility: Option<Duration>,
+    // Rounds a player stays frozen and the power it thaws to
+    pub thaw: Option<(u64, u64)>,
+}
+
+/// A transition fired by the scheduler
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Effect {
+    // An invisible player becomes visible
+    Reappear,
+    // A frozen player gets its power back
+    Thaw,
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Effect::Reappear => write!(f, "reappear"),
+            Effect::Thaw => write!(f, "thaw"),
+        }
+    }
+}
+
+/// Effect waiting for its moment
+#[derive(Clone, Debug)]
+struct Timer {
+    player: String,
+    effect: Effect,
+    // Value of the clock of the duration when the effect fires
+    due: Duration,
+}
+
+/// Clocks of the game and the effects waiting on them
+#[derive(Clone, Default, Debug)]
+pub struct Scheduler {
+    effects: Effects,
+    // Played actions, warnings included
+    actions: u64,
+    // Finished rounds
+    rounds: u64,
+    timers: Vec<Timer>,
+}
+
+impl Game {
+    /// Makes invisibility and freezing expire, players invisible or frozen
+    /// from the start get their timers too
+    pub fn enable_effects(&mut self, effects: Effects) {
+        self.scheduler.effects = effects;
+        let mut names: Vec<String> = self.players.keys().cloned().collect();
+        names.sort();
+        self.schedule(&names);
+    }
+
+    /// Stops every timer of the player, for players which are merged
+    pub fn cancel_timers(&mut self, name: &str) {
+        self.scheduler.timers.retain(|timer| timer.player != name);
+    }
+
+    /// Checks whether frozen players get their power back
+    pub fn thaws(&self) -> bool {
+        self.scheduler.effects.thaw.is_some()
+    }
+
+    /// Starts timers of players which became invisible or frozen and stops timers
+    /// of players which stopped being so. A player has at most one timer per effect
+    fn schedule(&mut self, names: &[String]) {
+        let Scheduler {
+            effects,
+            actions,
+            rounds,
+            ..
+        } = self.scheduler;
+        for name in names {
+            let Some(player) = self.players.get(name) else {
+                continue;
+            };
+            let wanted = [
+                (
+                    Effect::Reappear,
+                    effects.invisibility.filter(|_| !player.is_visible),
+                ),
+                (
+                    Effect::Thaw,
+                    effects
+                        .thaw
+                        .filter(|_| player.power == 0)
+                        .map(|(rounds, _)| Duration::Rounds(rounds)),
+                ),
+            ];
+
+            for (effect, duration) in wanted {
+                let timers = &mut self.scheduler.timers;
+                let running = timers
+                    .iter()
+                    .any(|timer| timer.player == *name && timer.effect == effect);
+                match duration {
+                    None => {
+                        timers.retain(|timer| !(timer.player == *name && timer.effect == effect))
+                    }
+                    Some(_) if running => {}
+                    Some(duration) => timers.push(Timer {
+                        player: name.clone(),
+                        effect,
+                        due: match duration {
+                            Duration::Actions(amount) => Duration::Actions(actions + amount),
+                            Duration::Rounds(amount) => Duration::Rounds(rounds + amount),
+                        },
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Counts played actions, updates timers of the changed players
+    /// and fires the effects which are due
+    pub fn tick_actions(&mut self, played: u64, changed: &[String]) {
+        self.scheduler.actions += played;
+        self.schedule(changed);
+        self.fire();
+    }
+
+    /// Counts a finished round and fires the effects which are due
+    pub fn tick_round(&mut self) {
+        self.scheduler.rounds += 1;
+        self.fire();
+    }
+
+    /// Applies the due effects in the order of player names and logs them as events
+    fn fire(&mut self) {
+        let (actions, rounds) = (self.scheduler.actions, self.scheduler.rounds);
+        let (mut due, timers): (Vec<Timer>, Vec<Timer>) = self
+            .scheduler
+            .timers
+            .drain(..)
+            .partition(|timer| match timer.due {
+                Duration::Actions(moment) => moment <= actions,
+                Duration::Rounds(moment) => moment <= rounds,
+            });
+        self.scheduler.timers = timers;
+        due.sort_by(|first, second| first.player.cmp(&second.player));
+
+        for timer in due {
+            let Some(player) = self.players.get_mut(&timer.player) else {
+                continue;
+            };
+            match timer.effect {
+                Effect::Reappear => player.is_visible = true,
+                Effect::Thaw => {
+                    player.power = self.scheduler.effects.thaw.map_or(0, |(_, power)| power)
+                }
+            }
+            self.events.push(Event {
+                action: None,
+                outcome: Outcome::Expired(timer.effect, player.clone()),
+                involved: Vec::new(),
+            });
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::fs::File;
+
+use crate::{
+    minimize::without_team,
+    random::Random,
+    scenario::Scenario,
+    strategy::{play_match, strategy_by_name},
+};
+
+/// Settings of an election
+#[derive(Clone, Debug)]
+pub struct ElectionConfig {
+    // Games played in every round
+    pub games: usize,
+    // Maximal amount of actions in a game
+    pub max_actions: usize,
+    // Rounds after the first one before the tie is broken by the order of the teams
+    pub max_runoffs: usize,
+    pub seed: u64,
+}
+
+impl Default for ElectionConfig {
+    fn default() -> Self {
+        Self {
+            games: 11,
+            max_actions: 100,
+            max_runoffs: 10,
+            seed: 0,
+        }
+    }
+}
+
+/// A series of games between some of the teams
+#[derive(PartialEq, Clone, Debug)]
+pub struct Round {
+    // Teams taking part, as indices of the roster
+    pub teams: Vec<usize>,
+    // Chosen team of every game, None for a tie
+    pub winners: Vec<Option<usize>>,
+    // How many times every team of `teams` was chosen
+    pub votes: Vec<u64>,
+}
+
+impl Round {
+    /// Teams chosen the largest amount of times
+    pub fn leaders(&self) -> Vec<usize> {
+        let best = self.votes.iter().copied().max().unwrap_or(0);
+        self.teams
+            .iter()
+            .zip(&self.votes)
+            .filter(|(_, &votes)| votes == best)
+            .map(|(&team, _)| team)
+            .collect()
+    }
+}
+
+/// Full record of an election
+#[derive(PartialEq, Clone, Debug)]
+pub struct Election {
+    pub rounds: Vec<Round>,
+    // Team whose magician becomes the club head
+    pub club_head: usize,
+    // The last round was still tied, the team earliest in the roster was taken
+    pub by_order: bool,
+}
+
+/// Plays `games` games between the teams with the strategies of the roster's teams
+/// (one strategy name per team). Game numbers continue between rounds, so every game
+/// of the election has its own seed
+fn play_round(
+    roster: &Scenario,
+    strategies: &[String],
+    teams: Vec<usize>,
+    config: &ElectionConfig,
+    first_game: usize,
+) -> Round {
+    // Only the teams of the round stay in the roster, renumbered in their order
+    let mut scenario = roster.clone();
+    for team in (0..roster.teams.len()).rev() {
+        if !teams.contains(&team) {
+            scenario = without_team(&scenario, team);
+        }
+    }
+
+    let mut winners = Vec::new();
+    let mut votes = vec![0; teams.len()];
+    for game in first_game..first_game + config.games {
+        let seed = Random::stream(config.seed, game as u64).next_u64();
+        let mut bots: Vec<_> = teams
+            .iter()
+            .enumerate()
+            .map(|(index, &team)| {
+                let seed = Random::stream(seed, index as u64).next_u64();
+                strategy_by_name(&strategies[team], seed).unwrap()
+            })
+            .collect();
+        let winner = play_match(&scenario, &mut bots, config.max_actions)
+            .play()
+            .unwrap()
+            .get_winner_index();
+        if let Some(index) = winner {
+            votes[index] += 1;
+        }
+        winners.push(winner.map(|index| teams[index]));
+    }
+
+    Round {
+        teams,
+        winners,
+        votes,
+    }
+}
+
+/// Elects the club head: every team plays a round of games, the team chosen
+/// the most times wins. Ties are resolved by runoff rounds between the tied
+/// teams only, after `max_runoffs` runoffs the tied team earliest in the roster wins
+///
+/// `strategies` are names of built-in strategies, one per team
+pub fn elect(roster: &Scenario, strategies: &[String], config: &ElectionConfig) -> Election {
+    let mut rounds: Vec<Round> = Vec::new();
+    let mut teams: Vec<usize> = (0..roster.teams.len()).collect();
+    loop {
+        let first_game = rounds.len() * config.games;
+        let round = play_round(roster, strategies, teams, config, first_game);
+        let leaders = round.leaders();
+        rounds.push(round);
+
+        if leaders.len() == 1 || rounds.len() > config.max_runoffs {
+            return Election {
+                rounds,
+                club_head: leaders[0],
+                by_order: leaders.len() > 1,
+            };
+        }
+        teams = leaders;
+    }
+}
+
+/// `elect <roster file> <strategy>... [--games N] [--actions K] [--runoffs R] [--seed S]`
+/// command. Runs an election between bots (one per team, or one for all teams)
+/// and prints the audit of every round
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage = "Usage: elect <roster file> (random|greedy|turtle|merge)... \
+                 [--games N] [--actions K] [--runoffs R] [--seed S]";
+    let path = args.first().ok_or(usage)?;
+
+    let mut strategies = Vec::new();
+    let mut config = ElectionConfig::default();
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        if !arg.starts_with("--") {
+            strategy_by_name(arg, 0).ok_or_else(|| format!("Unknown strategy `{}`", arg))?;
+            strategies.push(arg.clone());
+            continue;
+        }
+        let value = rest.next().ok_or(usage)?;
+        let invalid = || format!("Invalid value `{}` of `{}`", value, arg);
+        match arg.as_str() {
+            "--games" => config.games = value.parse().map_err(|_| invalid())?,
+            "--actions" => config.max_actions = value.parse().map_err(|_| invalid())?,
+            "--runoffs" => config.max_runoffs = value.parse().map_err(|_| invalid())?,
+            "--seed" => config.seed = value.parse().map_err(|_| invalid())?,
+            _ => return Err(usage.to_string()),
+        }
+    }
+    if config.games == 0 {
+        return Err("At least one game is needed in every round".to_string());
+    }
+
+    let mut input = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let roster = Scenario::read_roster(&mut input).map_err(|e| format!("{}: {}", path, e))?;
+    let teams = roster.teams.len();
+    if strategies.len() == 1 {
+        strategies = vec![strategies[0].clone(); teams];
+    }
+    if strategies.len() != teams {
+        return Err(format!("Expected 1 or {} strategies", teams));
+    }
+
+    let election = elect(&roster, &strategies, &config);
+    let name = |team: usize| roster.teams[team].as_str();
+    let names = |teams: &[usize]| {
+        teams
+            .iter()
+            .map(|&team| name(team))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    for (number, round) in election.rounds.iter().enumerate() {
+        if number == 0 {
+            println!("Round 1: {}", names(&round.teams));
+        } else {
+            println!("Runoff {}: {}", number, names(&round.teams));
+        }
+        for (game, winner) in round.winners.iter().enumerate() {
+            println!("  Game {}: {}", game + 1, winner.map_or("tie", name));
+        }
+        let votes: Vec<String> = round
+            .teams
+            .iter()
+            .zip(&round.votes)
+            .map(|(&team, votes)| format!("{} {}", name(team), votes))
+            .collect();
+        println!("  Chosen: {}", votes.join(", "));
+        let leaders = round.leaders();
+        if leaders.len() > 1 {
+            println!("  Tie between {}", names(&leaders));
+        }
+    }
+    if election.by_order {
+        println!(
+            "The tie was not resolved after {} runoffs, the first team of the roster is taken",
+            config.max_runoffs
+        );
+    }
+    println!("The club head is {}", name(election.club_head));
+    Ok(())
+}
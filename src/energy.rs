@@ -0,0 +1,129 @@
+use crate::{Action, Game, Player};
+
+/// Costs of actions in energy, see `Rules::energy`. Players start with full energy
+/// and get `regeneration` back at the end of every round
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Energy {
+    pub attack: u64,
+    pub heal: u64,
+    pub flip: u64,
+    pub super_action: u64,
+    pub shield: u64,
+    pub revive: u64,
+    pub transfer: u64,
+    pub sacrifice: u64,
+    pub split: u64,
+    pub regeneration: u64,
+    // Most energy a player can have
+    pub capacity: u64,
+}
+
+impl Default for Energy {
+    fn default() -> Self {
+        Self {
+            attack: 3,
+            heal: 2,
+            flip: 1,
+            super_action: 4,
+            shield: 2,
+            revive: 4,
+            transfer: 1,
+            sacrifice: 0,
+            split: 2,
+            regeneration: 3,
+            capacity: 10,
+        }
+    }
+}
+
+impl Energy {
+    /// Parses comma separated `name:value` pairs, names are `attack`, `heal`, `flip`,
+    /// `super`, `shield`, `revive`, `transfer`, `sacrifice`, `split`, `regen` and `max`.
+    /// Missing values are the default ones, an empty text gives the default costs
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut energy = Energy::default();
+        for pair in text.split(',').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once(':')?;
+            let value = value.parse().ok()?;
+            match name {
+                "attack" => energy.attack = value,
+                "heal" => energy.heal = value,
+                "flip" => energy.flip = value,
+                "super" => energy.super_action = value,
+                "shield" => energy.shield = value,
+                "revive" => energy.revive = value,
+                "transfer" => energy.transfer = value,
+                "sacrifice" => energy.sacrifice = value,
+                "split" => energy.split = value,
+                "regen" => energy.regeneration = value,
+                "max" => energy.capacity = value,
+                _ => return None,
+            }
+        }
+        Some(energy)
+    }
+
+    /// Energy the action costs its actor
+    pub fn cost(&self, action: &Action) -> u64 {
+        match action {
+            Action::Attack(_, _) => self.attack,
+            Action::Heal(_, _) => self.heal,
+            Action::FlipVisibility(_) => self.flip,
+            Action::Super(_, _) => self.super_action,
+            Action::Shield(_, _) => self.shield,
+            Action::Revive(_, _) => self.revive,
+            Action::Transfer(_, _, _) => self.transfer,
+            Action::Sacrifice(_) => self.sacrifice,
+            Action::Split(_) => self.split,
+        }
+    }
+}
+
+impl Game {
+    /// Gives every player full energy, actions cost energy from now on
+    pub fn enable_energy(&mut self, energy: Energy) {
+        self.energy = Some(energy);
+        for player in self.players.values_mut() {
+            player.energy = Some(energy.capacity);
+        }
+    }
+
+    /// Checks whether the actor can not pay for the action, see `warning_of`
+    /// for the order of the warnings. The actor has to exist
+    pub fn lacks_energy(&self, action: &Action) -> bool {
+        let Some(energy) = self.energy else {
+            return false;
+        };
+        let actor = &self.players[action.actor()];
+        actor.energy.is_some_and(|left| left < energy.cost(action))
+    }
+
+    /// Takes the cost of the action from its actor, once the action is known
+    /// to be performed
+    pub fn pay_energy(&mut self, action: &Action) {
+        let Some(energy) = self.energy else {
+            return;
+        };
+        let actor = self.players.get_mut(action.actor()).unwrap();
+        actor.energy = actor.energy.map(|left| left - energy.cost(action));
+    }
+
+    /// Energy of a super player made of the players
+    pub fn merged_energy(&self, parts: &[Player]) -> Option<u64> {
+        let capacity = self.energy?.capacity;
+        let total = parts.iter().map(|part| part.energy).sum::<Option<u64>>()?;
+        Some(total.min(capacity))
+    }
+
+    /// Gives every player the energy regenerated in a round
+    pub fn regenerate_energy(&mut self) {
+        let Some(energy) = self.energy else {
+            return;
+        };
+        for player in self.players.values_mut() {
+            player.energy = player
+                .energy
+                .map(|left| (left + energy.regeneration).min(energy.capacity));
+        }
+    }
+}
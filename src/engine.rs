@@ -0,0 +1,294 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    process::{Command, Stdio},
+};
+
+use crate::{
+    random::Random,
+    scenario::Scenario,
+    strategy::{strategy_by_name, Strategy},
+    view::Fog,
+    Action, Game,
+};
+
+/// Commands understood by the engine, one per line:
+/// - `isready`: answered with `readyok`
+/// - `roster`, the roster in the input format and `end`: starts a new game, answered with `ok`
+/// - `action <action>`: performs the action, answered with `ok <event>` or `warning <warning>`
+/// - `state`: answered with `team <magician> <power>` per team,
+///   `player <name> <team> <power> <visibility> [<energy>]` per player and `end`
+/// - `view <magician> [power|existence]`: answered like `state`, but as the team sees it:
+///   invisible enemies are shown without their power (`?`), or not shown at all
+/// - `events`: answered with `event <event>` per played action and `end`
+/// - `verdict`: answered with `verdict <verdict>`
+/// - `go <magician>`: answered with `bestmove <action>` or `bestmove pass`
+/// - `quit`: stops the engine
+///
+/// Anything wrong is answered with `error <message>`, the game is not changed
+pub struct Engine {
+    game: Option<Game>,
+    strategy: Box<dyn Strategy>,
+}
+
+impl Engine {
+    /// Engine choosing its moves with the strategy
+    pub fn new(strategy: Box<dyn Strategy>) -> Self {
+        Self {
+            game: None,
+            strategy,
+        }
+    }
+
+    /// Answers a single command. `input` gives the lines following a `roster` command
+    ///
+    /// Returns the lines of the answer, None for `quit`
+    fn answer(
+        &mut self,
+        command: &str,
+        input: &mut impl Iterator<Item = io::Result<String>>,
+    ) -> Option<Vec<String>> {
+        let (verb, argument) = command.split_once(' ').unwrap_or((command, ""));
+        if verb == "roster" {
+            return Some(vec![self.load(input)]);
+        }
+        if verb == "isready" {
+            return Some(vec!["readyok".to_string()]);
+        }
+        if verb == "quit" {
+            return None;
+        }
+
+        let Some(game) = self.game.as_mut() else {
+            return Some(vec!["error No roster".to_string()]);
+        };
+        let answer = match verb {
+            "action" => {
+                // Effects fired by the action are logged after its outcome
+                let (warnings, events) = (game.warnings.len(), game.events.len());
+                match Action::parse(argument).and_then(|action| game.play(&action)) {
+                    Err(e) => vec![format!("error {}", e)],
+                    Ok(()) if game.warnings.len() > warnings => {
+                        vec![format!("warning {}", game.warnings[warnings])]
+                    }
+                    Ok(()) => vec![format!("ok {}", game.events[events])],
+                }
+            }
+            "state" => game.view_lines(None, Fog::Off),
+            "view" => {
+                let (magician, fog) = argument.split_once(' ').unwrap_or((argument, "power"));
+                match (
+                    game.teams.iter().position(|name| name == magician),
+                    Fog::parse(fog),
+                ) {
+                    (Some(team), Some(fog)) => game.view_lines(Some(team), fog),
+                    _ => vec![format!("error Unknown view `{}`", argument)],
+                }
+            }
+            "events" => game
+                .events
+                .iter()
+                .map(|event| format!("event {}", event))
+                .chain(["end".to_string()])
+                .collect(),
+            "verdict" => vec![format!("verdict {}", game.verdict())],
+            "go" => match game.teams.iter().position(|name| name == argument) {
+                None => vec![format!("error Unknown magician `{}`", argument)],
+                Some(team) => match self.strategy.choose(game, team) {
+                    Some(action) => vec![format!("bestmove {}", action)],
+                    None => vec!["bestmove pass".to_string()],
+                },
+            },
+            _ => vec![format!("error Unknown command `{}`", verb)],
+        };
+        Some(answer)
+    }
+
+    /// Reads roster lines up to `end` and starts a new game from them
+    fn load(&mut self, input: &mut impl Iterator<Item = io::Result<String>>) -> String {
+        let mut text = String::new();
+        for line in input.by_ref() {
+            match line {
+                Ok(line) if line == "end" => break,
+                Ok(line) => text += &(line + "\n"),
+                Err(_) => break,
+            }
+        }
+        match Scenario::read_roster(&mut text.as_bytes()) {
+            Ok(roster) => {
+                self.game = Some(roster.roster());
+                "ok".to_string()
+            }
+            Err(e) => format!("error {}", e),
+        }
+    }
+
+    /// Answers commands until `quit` or the end of the input
+    pub fn run(&mut self, input: impl BufRead, output: &mut impl Write) -> io::Result<()> {
+        let mut lines = input.lines();
+        while let Some(command) = lines.next() {
+            let Some(answer) = self.answer(command?.trim(), &mut lines) else {
+                break;
+            };
+            for line in answer {
+                writeln!(output, "{}", line)?;
+            }
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Connection to an engine controlling a single team
+pub struct Connection {
+    pub input: Box<dyn BufRead + Send>,
+    pub output: Box<dyn Write + Send>,
+}
+
+impl Connection {
+    /// Sends the command and reads a single line of the answer
+    fn ask(&mut self, command: &str) -> io::Result<String> {
+        writeln!(self.output, "{}", command)?;
+        self.output.flush()?;
+        let mut answer = String::new();
+        if self.input.read_line(&mut answer)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the engine closed the connection",
+            ));
+        }
+        Ok(answer.trim_end().to_string())
+    }
+}
+
+/// Plays a match between engines, one per team. Every engine gets the roster and
+/// every performed action, teams are asked for their `bestmove` in the order of their
+/// numbers. Moves of other teams' players, unknown players and `pass` count as a pass,
+/// the match ends after `max_actions` actions or when every team passed in a row
+///
+/// Returns the roster with the played actions, like `play_match`
+pub fn referee(
+    roster: &Scenario,
+    engines: &mut [Connection],
+    max_actions: usize,
+) -> io::Result<Scenario> {
+    let mut game = roster.roster();
+    let mut log = Scenario {
+        actions: Vec::new(),
+        ..roster.clone()
+    };
+
+    let text = roster.to_string();
+    for engine in engines.iter_mut() {
+        let command = format!("roster\n{}end", text);
+        if engine.ask(&command)? != "ok" {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the engine rejected the roster",
+            ));
+        }
+    }
+
+    let mut passes = 0;
+    let mut team = 0;
+    while log.actions.len() < max_actions && passes < engines.len() {
+        let answer = engines[team].ask(&format!("go {}", roster.teams[team]))?;
+        let action = answer
+            .strip_prefix("bestmove ")
+            .and_then(|line| Action::parse(line).ok())
+            .filter(|action| {
+                game.players
+                    .get(action.actor())
+                    .is_some_and(|player| player.team_number == team as u64)
+            });
+
+        match action {
+            Some(action) if game.perform(&action).is_ok() => {
+                for engine in engines.iter_mut() {
+                    engine.ask(&format!("action {}", action))?;
+                }
+                log.actions.push(action);
+                passes = 0;
+            }
+            _ => passes += 1,
+        }
+        team = (team + 1) % engines.len();
+    }
+
+    for engine in engines.iter_mut() {
+        writeln!(engine.output, "quit")?;
+        engine.output.flush()?;
+    }
+    Ok(log)
+}
+
+/// `engine [random|greedy|turtle|merge] [--seed S]` command. Speaks the engine protocol
+/// on the standard input and output, `go` is answered with the strategy (greedy by default)
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage = "Usage: engine [random|greedy|turtle|merge] [--seed S]";
+    let (name, seed) = match args {
+        [] => ("greedy", 0),
+        [name] => (name.as_str(), 0),
+        [flag, seed] if flag == "--seed" => ("greedy", seed.parse().map_err(|_| usage)?),
+        [name, flag, seed] if flag == "--seed" => (name.as_str(), seed.parse().map_err(|_| usage)?),
+        _ => return Err(usage.to_string()),
+    };
+    let strategy = strategy_by_name(name, Random::new(seed).next_u64())
+        .ok_or_else(|| format!("Unknown strategy `{}`", name))?;
+
+    Engine::new(strategy)
+        .run(io::stdin().lock(), &mut io::stdout().lock())
+        .map_err(|e| e.to_string())
+}
+
+/// `referee <roster file> <program>... [--actions K]` command. Starts a program
+/// per team (a command line, split on spaces), plays a match between them over
+/// the engine protocol and prints the log in the input format
+pub fn referee_command(args: &[String]) -> Result<(), String> {
+    let usage = "Usage: referee <roster file> <program>... [--actions K]";
+    let path = args.first().ok_or(usage)?;
+
+    let mut programs = Vec::new();
+    let mut max_actions = 100;
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        if arg == "--actions" {
+            let value = rest.next().and_then(|value| value.parse().ok());
+            max_actions = value.ok_or(usage)?;
+        } else {
+            programs.push(arg);
+        }
+    }
+
+    let mut input = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let roster = Scenario::read_roster(&mut input).map_err(|e| format!("{}: {}", path, e))?;
+    if programs.len() != roster.teams.len() {
+        return Err(format!("Expected {} programs", roster.teams.len()));
+    }
+
+    let mut children = Vec::new();
+    let mut engines = Vec::new();
+    for program in programs {
+        let mut words = program.split_whitespace();
+        let name = words.next().ok_or(usage)?;
+        let mut child = Command::new(name)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("{}: {}", name, e))?;
+        engines.push(Connection {
+            input: Box::new(BufReader::new(child.stdout.take().unwrap())),
+            output: Box::new(child.stdin.take().unwrap()),
+        });
+        children.push(child);
+    }
+
+    let log = referee(&roster, &mut engines, max_actions).map_err(|e| e.to_string())?;
+    drop(engines);
+    for mut child in children {
+        child.wait().map_err(|e| e.to_string())?;
+    }
+    print!("{}", log);
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+
+use crate::{effects::Effect, Action, Player};
+
+/// What a played action did
+#[derive(PartialEq, Clone, Debug)]
+pub enum Outcome {
+    // The action was ignored with the warning
+    Warning(&'static str),
+    // Players changed by the action, as they are after it
+    Changed(Vec<Player>),
+    // The action collided with other actions of the same simultaneous round
+    Conflict(String),
+    // A timed effect of the player expired, the player is as it is after that
+    Expired(Effect, Player),
+}
+
+/// Entry of the event log: a played action and its outcome
+#[derive(PartialEq, Clone, Debug)]
+pub struct Event {
+    // None for effects fired by the scheduler
+    pub action: Option<Action>,
+    pub outcome: Outcome,
+    // Players the action is about, as they were before it, the acting player first
+    pub involved: Vec<Player>,
+}
+
+/// Name, power and visibility of the player, as in the input file,
+/// followed by the energy if actions cost energy
+pub fn describe(player: &Player) -> String {
+    let visibility = if player.is_visible { "True" } else { "False" };
+    match player.energy {
+        Some(energy) => format!(
+            "{} {} {} energy {}",
+            player.name, player.power, visibility, energy
+        ),
+        None => format!("{} {} {}", player.name, player.power, visibility),
+    }
+}
+
+impl fmt::Display for Event {
+    /// Writes the action and its outcome: the warning, the conflict,
+    /// or the name, power and visibility of every changed player.
+    /// An expired effect is written as `<effect> <player>: <player after it>`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = self
+            .action
+            .as_ref()
+            .map_or(String::new(), Action::to_string);
+        match &self.outcome {
+            Outcome::Warning(warning) => write!(f, "{}: {}", action, warning),
+            Outcome::Conflict(message) => write!(f, "{}: conflict: {}", action, message),
+            Outcome::Changed(players) => {
+                let players: Vec<String> = players.iter().map(describe).collect();
+                write!(f, "{}: {}", action, players.join(", "))
+            }
+            Outcome::Expired(effect, player) => {
+                write!(f, "{} {}: {}", effect, player.name, describe(player))
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::fs;
+
+use crate::{rules::Rules, scenario::Scenario};
+
+/// Rewrites a scenario in canonical form. The scenario is read with tolerant input,
+/// so trailing spaces, CRLF line endings and repeated spaces are accepted
+///
+/// Returns Err(INVALID_INPUT) if the scenario can not be parsed even tolerantly
+pub fn canonical(text: &str) -> Result<String, &'static str> {
+    let rules = Rules {
+        tolerant_input: true,
+        ..Rules::default()
+    };
+    Ok(Scenario::read(&mut text.as_bytes(), &rules)?.to_string())
+}
+
+/// `fmt [--check] <file>...` command. Rewrites files in place, or with `--check`
+/// only reports files which are not canonical and fails if there are any
+pub fn command(args: &[String]) -> Result<(), String> {
+    let check = args.iter().any(|arg| arg == "--check");
+    let paths: Vec<&String> = args.iter().filter(|arg| *arg != "--check").collect();
+    if paths.is_empty() {
+        return Err("Usage: fmt [--check] <file>...".to_string());
+    }
+
+    let mut not_canonical = 0;
+    for path in paths {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let formatted = canonical(&text).map_err(|e| format!("{}: {}", path, e))?;
+        if formatted == text {
+            continue;
+        }
+
+        if check {
+            println!("{}: not canonical", path);
+            not_canonical += 1;
+        } else {
+            fs::write(path, formatted).map_err(|e| format!("{}: {}", path, e))?;
+            println!("{}: formatted", path);
+        }
+    }
+
+    if not_canonical > 0 {
+        return Err(format!("{} file(s) not canonical", not_canonical));
+    }
+    Ok(())
+}
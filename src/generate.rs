@@ -0,0 +1,353 @@
+use std::fs;
+
+use crate::{random::Random, scenario::Scenario, Action, Game, Player};
+
+/// How powers of the generated players are distributed in the power range
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PowerDistribution {
+    // Every power is equally likely
+    Uniform,
+    // Powers near the lower bound are more likely
+    Weak,
+    // Powers near the upper bound are more likely
+    Strong,
+}
+
+/// Kind of the error injected into a scenario in the invalid mode
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum InvalidKind {
+    // The amount of teams is out of \[1, 10\]
+    TeamCount,
+    // The amount of players is less than the amount of teams
+    PlayerCount,
+    // A magician name starts with a lowercase letter
+    MagicianName,
+    // A player name contains a digit
+    PlayerName,
+    // A player has the team number equal to the amount of teams
+    TeamNumber,
+    // A player has power more than 1000
+    Power,
+    // A visibility is neither "True" nor "False"
+    Visibility,
+    // An action with unknown name
+    UnknownAction,
+    // An action with an extra word
+    Arity,
+    // An action with a player which does not exist
+    MissingPlayer,
+}
+
+impl InvalidKind {
+    pub const ALL: [InvalidKind; 10] = [
+        InvalidKind::TeamCount,
+        InvalidKind::PlayerCount,
+        InvalidKind::MagicianName,
+        InvalidKind::PlayerName,
+        InvalidKind::TeamNumber,
+        InvalidKind::Power,
+        InvalidKind::Visibility,
+        InvalidKind::UnknownAction,
+        InvalidKind::Arity,
+        InvalidKind::MissingPlayer,
+    ];
+
+    /// Name of the kind on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            InvalidKind::TeamCount => "team-count",
+            InvalidKind::PlayerCount => "player-count",
+            InvalidKind::MagicianName => "magician-name",
+            InvalidKind::PlayerName => "player-name",
+            InvalidKind::TeamNumber => "team-number",
+            InvalidKind::Power => "power",
+            InvalidKind::Visibility => "visibility",
+            InvalidKind::UnknownAction => "unknown-action",
+            InvalidKind::Arity => "arity",
+            InvalidKind::MissingPlayer => "missing-player",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        InvalidKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+    }
+}
+
+/// Knobs of the scenario generator
+#[derive(Clone, Debug)]
+pub struct GeneratorConfig {
+    pub seed: u64,
+    // Amount of teams, \[1, 10\]
+    pub teams: usize,
+    // Amount of players, \[teams, 100\]
+    pub players: usize,
+    // Bounds of the initial power, inside \[0, 1000\]
+    pub power_min: u64,
+    pub power_max: u64,
+    pub power_distribution: PowerDistribution,
+    // Probability of a player to be visible initially
+    pub visibility_ratio: f64,
+    // Amount of actions, \[0, 1000\]
+    pub actions: usize,
+    // Weights of attack, heal, flip_visibility and super actions
+    pub action_mix: [u64; 4],
+    // Error to inject, None generates a valid scenario
+    pub invalid: Option<InvalidKind>,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            teams: 2,
+            players: 6,
+            power_min: 0,
+            power_max: 1000,
+            power_distribution: PowerDistribution::Uniform,
+            visibility_ratio: 0.8,
+            actions: 20,
+            action_mix: [4, 2, 2, 1],
+            invalid: None,
+        }
+    }
+}
+
+/// Name made of letters only: 0 -> A, 25 -> Z, 26 -> AA and so on
+fn letters(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+/// Draws the initial power of a player
+fn draw_power(config: &GeneratorConfig, random: &mut Random) -> u64 {
+    let first = random.range(config.power_min, config.power_max);
+    let second = random.range(config.power_min, config.power_max);
+    match config.power_distribution {
+        PowerDistribution::Uniform => first,
+        PowerDistribution::Weak => first.min(second),
+        PowerDistribution::Strong => first.max(second),
+    }
+}
+
+/// Draws the next action. The game is played along, so players removed by super
+/// actions are never used afterwards
+fn draw_action(config: &GeneratorConfig, game: &Game, random: &mut Random) -> Action {
+    // Names are sorted, as the order of the players hashmap is not deterministic
+    let mut names: Vec<String> = game.players.keys().cloned().collect();
+    names.sort();
+
+    let actor = random.pick(&names).clone();
+    let team = game.players[&actor].team_number;
+    let teammates: Vec<String> = names
+        .iter()
+        .filter(|name| **name != actor && game.players[*name].team_number == team)
+        .cloned()
+        .collect();
+    let kind = random.weighted(&config.action_mix);
+    // Heal and super mostly make sense inside a team
+    let mut teammate = || {
+        if teammates.is_empty() {
+            actor.clone()
+        } else {
+            random.pick(&teammates).clone()
+        }
+    };
+
+    match kind {
+        0 => {
+            let target = random.pick(&names).clone();
+            Action::Attack(actor, target)
+        }
+        1 => Action::Heal(actor.clone(), teammate()),
+        2 => Action::FlipVisibility(actor),
+        _ => Action::Super(actor.clone(), vec![teammate()]),
+    }
+}
+
+/// Generates a valid scenario from the configuration. The invalid mode is ignored
+pub fn generate(config: &GeneratorConfig) -> Scenario {
+    let mut random = Random::new(config.seed);
+
+    let teams: Vec<String> = (0..config.teams)
+        .map(|index| format!("Wizard{}", letters(index)))
+        .collect();
+
+    let players: Vec<Player> = (0..config.players)
+        .map(|index| Player {
+            name: format!("Player{}", letters(index)),
+            // Every team gets at least one player if there are enough of them
+            team_number: if index < config.teams {
+                index as u64
+            } else {
+                random.below(config.teams as u64)
+            },
+            power: draw_power(config, &mut random),
+            is_visible: random.chance(config.visibility_ratio),
+            energy: None,
+        })
+        .collect();
+
+    let mut game = Game::new(teams.clone(), players.clone());
+    let mut actions = Vec::with_capacity(config.actions);
+    for _ in 0..config.actions {
+        let action = draw_action(config, &game, &mut random);
+        // Only existing players are used, so the action can not be invalid
+        game.perform(&action).unwrap();
+        actions.push(action);
+    }
+
+    Scenario {
+        teams,
+        players,
+        actions,
+    }
+}
+
+/// Generates the text of a scenario. In the invalid mode exactly one error
+/// of the configured kind is injected into a valid scenario
+pub fn generate_text(config: &GeneratorConfig) -> String {
+    let scenario = generate(config);
+    let kind = match config.invalid {
+        None => return scenario.to_string(),
+        Some(kind) => kind,
+    };
+
+    // The injection has its own random numbers, so the valid part stays the same
+    let mut random = Random::new(!config.seed);
+    let mut lines: Vec<String> = scenario.to_string().lines().map(String::from).collect();
+    let teams = scenario.teams.len();
+    let roster = teams + 2 + 4 * scenario.players.len();
+    // Index of the line with the given field (0 name, 1 team, 2 power, 3 visibility)
+    // of a random player
+    let mut player_line =
+        |field: usize| teams + 2 + 4 * random.below(scenario.players.len() as u64) as usize + field;
+
+    match kind {
+        InvalidKind::TeamCount => lines[0] = "11".to_string(),
+        InvalidKind::PlayerCount => {
+            // Keep the roster consistent with the new amount of players
+            lines.truncate(roster - 4 * (scenario.players.len() - teams + 1));
+            lines[teams + 1] = (teams - 1).to_string();
+        }
+        InvalidKind::MagicianName => {
+            let index = 1 + random.below(teams as u64) as usize;
+            lines[index] = lines[index].to_lowercase();
+        }
+        InvalidKind::PlayerName => {
+            let index = player_line(0);
+            lines[index].push('7');
+        }
+        InvalidKind::TeamNumber => {
+            let index = player_line(1);
+            lines[index] = teams.to_string();
+        }
+        InvalidKind::Power => {
+            let index = player_line(2);
+            lines[index] = "1001".to_string();
+        }
+        InvalidKind::Visibility => {
+            let index = player_line(3);
+            lines[index] = lines[index].to_lowercase();
+        }
+        InvalidKind::UnknownAction => lines.push(format!("cast {}", scenario.players[0].name)),
+        InvalidKind::Arity => {
+            lines.push(format!("flip_visibility {0} {0}", scenario.players[0].name))
+        }
+        InvalidKind::MissingPlayer => lines.push("flip_visibility Nobody".to_string()),
+    }
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// Parses `<min>-<max>` power bounds
+fn parse_power(value: &str) -> Option<(u64, u64)> {
+    let (min, max) = value.split_once('-')?;
+    let (min, max) = (min.parse().ok()?, max.parse().ok()?);
+    (min <= max && max <= 1000).then_some((min, max))
+}
+
+/// Parses `<attack>,<heal>,<flip>,<super>` weights
+fn parse_mix(value: &str) -> Option<[u64; 4]> {
+    let weights: Vec<u64> = value
+        .split(',')
+        .map(|weight| weight.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    let weights: [u64; 4] = weights.try_into().ok()?;
+    (weights.iter().sum::<u64>() > 0).then_some(weights)
+}
+
+/// Builds the configuration from command line flags
+fn parse_config(args: &[String]) -> Result<(GeneratorConfig, Option<String>), String> {
+    let mut config = GeneratorConfig::default();
+    let mut output = None;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value of `{}`", flag))?;
+        let invalid = || format!("Invalid value `{}` of `{}`", value, flag);
+
+        match flag.as_str() {
+            "--seed" => config.seed = value.parse().map_err(|_| invalid())?,
+            "--teams" => config.teams = value.parse().map_err(|_| invalid())?,
+            "--players" => config.players = value.parse().map_err(|_| invalid())?,
+            "--power" => {
+                (config.power_min, config.power_max) = parse_power(value).ok_or_else(invalid)?
+            }
+            "--distribution" => {
+                config.power_distribution = match value.as_str() {
+                    "uniform" => PowerDistribution::Uniform,
+                    "weak" => PowerDistribution::Weak,
+                    "strong" => PowerDistribution::Strong,
+                    _ => return Err(invalid()),
+                }
+            }
+            "--visible" => {
+                config.visibility_ratio = value
+                    .parse()
+                    .ok()
+                    .filter(|ratio| (0.0..=1.0).contains(ratio))
+                    .ok_or_else(invalid)?
+            }
+            "--actions" => config.actions = value.parse().map_err(|_| invalid())?,
+            "--mix" => config.action_mix = parse_mix(value).ok_or_else(invalid)?,
+            "--invalid" => config.invalid = Some(InvalidKind::parse(value).ok_or_else(invalid)?),
+            "-o" => output = Some(value.clone()),
+            _ => return Err(format!("Unknown flag `{}`", flag)),
+        }
+    }
+
+    if !(1..=10).contains(&config.teams)
+        || !(config.teams..=100).contains(&config.players)
+        || config.actions > 1000
+    {
+        return Err("Expected 1-10 teams, teams-100 players and at most 1000 actions".to_string());
+    }
+    Ok((config, output))
+}
+
+/// `generate [--seed S] [--teams N] [--players M] [--power MIN-MAX]
+/// [--distribution uniform|weak|strong] [--visible RATIO] [--actions S]
+/// [--mix A,H,F,S] [--invalid KIND] [-o FILE]` command
+pub fn command(args: &[String]) -> Result<(), String> {
+    let (config, output) = parse_config(args)?;
+    let text = generate_text(&config);
+    match output {
+        Some(path) => fs::write(&path, text).map_err(|e| format!("{}: {}", path, e)),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
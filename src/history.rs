@@ -0,0 +1,300 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Read, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{scenario::Scenario, Game};
+
+/// Archive `play --archive` appends every match to
+pub const HISTORY_FILE: &str = "history.log";
+
+/// A single archived match
+#[derive(PartialEq, Clone, Debug)]
+pub struct Record {
+    // Seconds since the Unix epoch
+    pub timestamp: u64,
+    // The input file as it was played, even if it was rejected
+    pub scenario: String,
+    pub events: Vec<String>,
+    // The last line of the output
+    pub verdict: String,
+}
+
+impl Record {
+    /// Records the match played from the scenario at the current time
+    pub fn new(scenario: String, result: &Result<Game, &'static str>) -> Self {
+        let (events, verdict) = match result {
+            Ok(game) => (
+                game.events.iter().map(|event| event.to_string()).collect(),
+                game.verdict(),
+            ),
+            Err(e) => (Vec::new(), e.to_string()),
+        };
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs()),
+            scenario,
+            events,
+            verdict,
+        }
+    }
+
+    /// Serializes the record: the timestamp, the verdict, the length of the scenario
+    /// in bytes and the scenario itself, then the amount of events and the events
+    fn encode(&self) -> String {
+        let mut payload = format!(
+            "{}\n{}\n{}\n{}{}\n",
+            self.timestamp,
+            self.verdict,
+            self.scenario.len(),
+            self.scenario,
+            self.events.len()
+        );
+        for event in &self.events {
+            payload += event;
+            payload += "\n";
+        }
+        payload
+    }
+
+    /// Parses a record written by `encode`
+    fn decode(payload: &str) -> Option<Self> {
+        let (timestamp, rest) = payload.split_once('\n')?;
+        let (verdict, rest) = rest.split_once('\n')?;
+        let (length, rest) = rest.split_once('\n')?;
+        let length: usize = length.parse().ok()?;
+        // The scenario is kept byte for byte, line endings included
+        let scenario = rest.get(..length)?;
+
+        let mut lines = rest[length..].lines();
+        let total: usize = lines.next()?.parse().ok()?;
+        let events = (0..total)
+            .map(|_| lines.next().map(str::to_string))
+            .collect::<Option<Vec<String>>>()?;
+
+        Some(Self {
+            timestamp: timestamp.parse().ok()?,
+            scenario: scenario.to_string(),
+            events,
+            verdict: verdict.to_string(),
+        })
+    }
+}
+
+/// 64-bit FNV-1a hash, detects records which were written only partially
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3)
+    })
+}
+
+/// Parses the record starting at the offset. Every record is a `record <length> <checksum>`
+/// header line followed by the payload of `length` bytes
+///
+/// Returns the record and the offset after it, or Err with the offset the damaged
+/// record reaches (the end of the archive for a record torn by an interrupted append)
+fn parse_record(bytes: &[u8], at: usize) -> Result<(Record, usize), usize> {
+    let Some(end) = bytes[at..].iter().position(|&byte| byte == b'\n') else {
+        return Err(bytes.len());
+    };
+    let header = String::from_utf8_lossy(&bytes[at..at + end]);
+    let start = at + end + 1;
+    let [tag, length, hash] = header.split(' ').collect::<Vec<_>>()[..] else {
+        return Err(start);
+    };
+    let (Ok(length), Ok(hash)) = (length.parse::<usize>(), u64::from_str_radix(hash, 16)) else {
+        return Err(start);
+    };
+    let Some(payload) = bytes.get(start..start.saturating_add(length)) else {
+        return Err(bytes.len());
+    };
+    if tag != "record" || checksum(payload) != hash {
+        return Err(start + length);
+    }
+    let record = std::str::from_utf8(payload)
+        .ok()
+        .and_then(Record::decode)
+        .ok_or(start + length)?;
+    Ok((record, start + length))
+}
+
+/// Parses the archive. Reading stops at the first incomplete or damaged record
+///
+/// Returns the records, the length of the valid part of the archive and whether
+/// the rest is only the tail of an interrupted append
+fn parse(bytes: &[u8]) -> (Vec<Record>, usize, bool) {
+    let mut records = Vec::new();
+    let mut valid = 0;
+    while valid < bytes.len() {
+        match parse_record(bytes, valid) {
+            Ok((record, next)) => {
+                records.push(record);
+                valid = next;
+            }
+            Err(reach) => return (records, valid, reach >= bytes.len()),
+        }
+    }
+    (records, valid, true)
+}
+
+/// Reads all complete records of the archive, a missing archive is empty
+pub fn read(path: &str) -> io::Result<Vec<Record>> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(parse(&bytes).0),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Appends the record to the archive. A damaged tail left by an interrupted append
+/// is cut off first, the record is written with a single write and synced to the disk
+/// before returning, so earlier records are never touched
+///
+/// Returns an error and leaves the archive as it is if a record before the tail is damaged
+pub fn append(path: &str, record: &Record) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let (_, valid, torn) = parse(&bytes);
+    if !torn {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("damaged record at byte {}", valid),
+        ));
+    }
+    if valid < bytes.len() {
+        file.set_len(valid as u64)?;
+    }
+
+    let payload = record.encode();
+    let entry = format!(
+        "record {} {:016x}\n{}",
+        payload.len(),
+        checksum(payload.as_bytes()),
+        payload
+    );
+    file.write_all(entry.as_bytes())?;
+    file.sync_all()
+}
+
+/// Archives the match played from the input
+pub fn archive(path: &str, input: &[u8], result: &Result<Game, &'static str>) -> io::Result<()> {
+    let scenario = String::from_utf8_lossy(input).into_owned();
+    append(path, &Record::new(scenario, result))
+}
+
+/// Formats the timestamp as a UTC date and time
+pub fn format_timestamp(timestamp: u64) -> String {
+    let (days, seconds) = (timestamp / 86400, timestamp % 86400);
+    // Converts days since 1970-01-01 to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Which records to list
+#[derive(Clone, Default, Debug)]
+pub struct Filter {
+    pub player: Option<String>,
+    pub magician: Option<String>,
+    pub ties: bool,
+}
+
+impl Filter {
+    /// Checks the roster of the match for the player and the magician
+    /// and its verdict for a tie. A rejected roster matches only without names
+    pub fn matches(&self, record: &Record) -> bool {
+        let roster = Scenario::read_roster(&mut record.scenario.as_bytes()).ok();
+        let has_player = |name: &String| {
+            roster
+                .as_ref()
+                .is_some_and(|roster| roster.players.iter().any(|player| &player.name == name))
+        };
+        let has_magician = |name: &String| {
+            roster
+                .as_ref()
+                .is_some_and(|roster| roster.teams.contains(name))
+        };
+        self.player.as_ref().is_none_or(has_player)
+            && self.magician.as_ref().is_none_or(has_magician)
+            && (!self.ties || record.verdict == "It's a tie")
+    }
+}
+
+/// `history <archive> [--player NAME] [--magician NAME] [--ties]` lists archived matches,
+/// `history <archive> show <number>` prints a single match
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage = "Usage: history <archive> [--player NAME] [--magician NAME] [--ties] \
+                 | history <archive> show <number>";
+    let (path, args) = args.split_first().ok_or(usage)?;
+    let records = read(path).map_err(|e| format!("{}: {}", path, e))?;
+
+    if let [show, number] = args {
+        if show == "show" {
+            let record = number
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| records.get(number.checked_sub(1)?))
+                .ok_or_else(|| format!("No match number `{}`", number))?;
+            println!("Played at {}", format_timestamp(record.timestamp));
+            println!();
+            print!("{}", record.scenario);
+            println!();
+            for event in &record.events {
+                println!("{}", event);
+            }
+            println!("{}", record.verdict);
+            return Ok(());
+        }
+    }
+
+    let mut filter = Filter::default();
+    let mut flags = args.iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--player" => filter.player = Some(flags.next().ok_or(usage)?.clone()),
+            "--magician" => filter.magician = Some(flags.next().ok_or(usage)?.clone()),
+            "--ties" => filter.ties = true,
+            _ => return Err(usage.to_string()),
+        }
+    }
+
+    for (index, record) in records.iter().enumerate() {
+        if filter.matches(record) {
+            println!(
+                "{} {} {}",
+                index + 1,
+                format_timestamp(record.timestamp),
+                record.verdict
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use std::fmt;
+
+/// JSON value. Numbers are integers, nothing in the game needs fractions
+#[derive(PartialEq, Clone, Debug)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<Json>),
+    // Keys in the order they were written
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Creates an object from pairs of keys and values
+    pub fn object<'a>(fields: impl IntoIterator<Item = (&'a str, Json)>) -> Self {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// Value of the key, if this is an object with the key
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Parses a whole JSON text
+    ///
+    /// Returns None if the text is not valid JSON, has a fractional number
+    /// or is nested deeper than MAX_DEPTH
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            position: 0,
+        };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        (parser.position == parser.chars.len()).then_some(value)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<u64> for Json {
+    fn from(value: u64) -> Self {
+        Json::Number(value as i64)
+    }
+}
+
+impl fmt::Display for Json {
+    /// Writes compact JSON
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(value) => write!(f, "{}", value),
+            Json::String(value) => write_string(f, value),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Writes a quoted string with escapes
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for symbol in value.chars() {
+        match symbol {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            symbol if (symbol as u32) < 0x20 => write!(f, "\\u{:04x}", symbol as u32)?,
+            symbol => write!(f, "{}", symbol)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Most arrays and objects a value may be nested in, deeper texts would overflow the stack
+const MAX_DEPTH: usize = 64;
+
+/// Recursive descent parser over the characters of the text
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn skip_whitespace(&mut self) {
+        while self
+            .chars
+            .get(self.position)
+            .is_some_and(|symbol| symbol.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let symbol = *self.chars.get(self.position)?;
+        self.position += 1;
+        Some(symbol)
+    }
+
+    /// Consumes the expected word, such as `true`
+    fn word(&mut self, word: &str) -> Option<()> {
+        for expected in word.chars() {
+            (self.next()? == expected).then_some(())?;
+        }
+        Some(())
+    }
+
+    /// Parses a value inside `depth` arrays and objects
+    fn value(&mut self, depth: usize) -> Option<Json> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        match *self.chars.get(self.position)? {
+            'n' => self.word("null").map(|_| Json::Null),
+            't' => self.word("true").map(|_| Json::Bool(true)),
+            'f' => self.word("false").map(|_| Json::Bool(false)),
+            '"' => self.string().map(Json::String),
+            '[' => {
+                self.position += 1;
+                let mut values = Vec::new();
+                self.skip_whitespace();
+                if self.chars.get(self.position) == Some(&']') {
+                    self.position += 1;
+                    return Some(Json::Array(values));
+                }
+                loop {
+                    values.push(self.value(depth + 1)?);
+                    self.skip_whitespace();
+                    match self.next()? {
+                        ',' => continue,
+                        ']' => return Some(Json::Array(values)),
+                        _ => return None,
+                    }
+                }
+            }
+            '{' => {
+                self.position += 1;
+                let mut fields = Vec::new();
+                self.skip_whitespace();
+                if self.chars.get(self.position) == Some(&'}') {
+                    self.position += 1;
+                    return Some(Json::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.skip_whitespace();
+                    (self.next()? == ':').then_some(())?;
+                    fields.push((key, self.value(depth + 1)?));
+                    self.skip_whitespace();
+                    match self.next()? {
+                        ',' => continue,
+                        '}' => return Some(Json::Object(fields)),
+                        _ => return None,
+                    }
+                }
+            }
+            _ => {
+                let start = self.position;
+                if self.chars.get(self.position) == Some(&'-') {
+                    self.position += 1;
+                }
+                while self
+                    .chars
+                    .get(self.position)
+                    .is_some_and(|symbol| symbol.is_ascii_digit())
+                {
+                    self.position += 1;
+                }
+                let number: String = self.chars[start..self.position].iter().collect();
+                number.parse().ok().map(Json::Number)
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        (self.next()? == '"').then_some(())?;
+        let mut value = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Some(value),
+                '\\' => value.push(match self.next()? {
+                    '"' => '"',
+                    '\\' => '\\',
+                    '/' => '/',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'u' => {
+                        let code: String = (0..4).map(|_| self.next()).collect::<Option<_>>()?;
+                        let code = u32::from_str_radix(&code, 16).ok()?;
+                        // Surrogate pairs are not needed for names and actions
+                        char::from_u32(code)?
+                    }
+                    _ => return None,
+                }),
+                symbol => value.push(symbol),
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{Game, Player};
+
+/// How a split super player shares its power between its parts, see `Rules::split_rule`
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum SplitRule {
+    // In proportion to the powers of the parts when they were merged
+    #[default]
+    Proportional,
+    // Equally between the parts
+    Equal,
+}
+
+impl SplitRule {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "proportional" => Some(SplitRule::Proportional),
+            "equal" => Some(SplitRule::Equal),
+            _ => None,
+        }
+    }
+
+    /// Divides the power between the parts. What can not be divided goes
+    /// one point per part from the first part, so nothing is lost
+    fn shares(&self, power: u64, parts: &[Player]) -> Vec<u64> {
+        let weights: Vec<u64> = match self {
+            SplitRule::Proportional if parts.iter().any(|part| part.power > 0) => {
+                parts.iter().map(|part| part.power).collect()
+            }
+            // Parts without power share equally
+            _ => vec![1; parts.len()],
+        };
+        let total: u64 = weights.iter().sum();
+        let mut shares: Vec<u64> = weights
+            .iter()
+            .map(|weight| power * weight / total)
+            .collect();
+        let left = power - shares.iter().sum::<u64>();
+        shares
+            .iter_mut()
+            .take(left as usize)
+            .for_each(|share| *share += 1);
+        shares
+    }
+}
+
+impl Game {
+    /// Remembers the players the super player was made of, as they were when merged
+    pub fn record_lineage(&mut self, name: &str, parts: Vec<Player>) {
+        self.lineage.insert(name.to_string(), parts);
+    }
+
+    /// Replaces the players with a new super player of their team, with their power
+    /// summed up to 1000, and remembers them as its parts
+    pub fn fuse(&mut self, parts: Vec<Player>) -> Player {
+        // Effects of the parts do not wait for them to be split
+        for part in &parts {
+            self.players.remove(&part.name);
+            self.cancel_timers(&part.name);
+        }
+        // Shields of the parts and shields made by them are gone
+        self.shields.retain(|protected, shield| {
+            parts
+                .iter()
+                .all(|part| part.name != *protected && part.name != *shield)
+        });
+
+        let super_player = Player {
+            name: format!("S_{}", self.super_player_counter),
+            team_number: parts[0].team_number,
+            power: 1000.min(parts.iter().map(|part| part.power).sum()),
+            is_visible: true,
+            energy: self.merged_energy(&parts),
+        };
+        self.super_player_counter += 1;
+        // The players are kept, so the super player can be split back into them
+        self.record_lineage(&super_player.name, parts);
+        self.players
+            .insert(super_player.name.clone(), super_player.clone());
+        super_player
+    }
+
+    /// Players of the roster the player consists of, in the order they were merged
+    pub fn originals(&self, name: &str) -> Vec<String> {
+        match self.lineage.get(name) {
+            Some(parts) => parts
+                .iter()
+                .flat_map(|part| self.originals(&part.name))
+                .collect(),
+            None => vec![name.to_string()],
+        }
+    }
+
+    /// Names of the players the super player was made of
+    pub fn parts(&self, name: &str) -> Option<Vec<String>> {
+        let parts = self.lineage.get(name)?;
+        Some(parts.iter().map(|part| part.name.clone()).collect())
+    }
+
+    /// Replaces the super player with the players it was made of, visible and with
+    /// the power and the energy of the super player divided by the split rule. Parts which
+    /// are super players themselves come back whole and can be split again
+    pub fn split(&mut self, name: &str) {
+        let parts = self.lineage.remove(name).unwrap();
+        let super_player = self.players.remove(name).unwrap();
+        // Shields of the super player and shields made by it are gone
+        self.shields
+            .retain(|protected, shield| protected != name && shield != name);
+        let shares = self.split_rule.shares(super_player.power, &parts);
+        let energy: Vec<Option<u64>> = match super_player.energy {
+            Some(energy) => self
+                .split_rule
+                .shares(energy, &parts)
+                .into_iter()
+                .map(Some)
+                .collect(),
+            None => vec![None; parts.len()],
+        };
+        for ((part, power), energy) in parts.into_iter().zip(shares).zip(energy) {
+            self.players.insert(
+                part.name.clone(),
+                Player {
+                    power,
+                    is_visible: true,
+                    energy,
+                    ..part
+                },
+            );
+        }
+    }
+}
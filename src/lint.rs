@@ -0,0 +1,136 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+};
+
+use crate::{
+    Action, Game, CANT_PLAY, DIFFERENT_TEAM, FROZEN_PLAYER, INVALID_INPUT, TRY_HEAL_ITSELF,
+    TRY_SUPER_ITSELF,
+};
+
+/// Structure representing a single suspicious place of a scenario
+#[derive(PartialEq, Debug)]
+pub struct Finding {
+    // Line of the scenario file (starting from 1)
+    pub line: usize,
+    // Short name of the kind of the problem
+    pub category: &'static str,
+    // Human readable description
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.line, self.category, self.message)
+    }
+}
+
+/// Maps a warning of the game into the category of a finding
+fn warning_category(warning: &str) -> &'static str {
+    match warning {
+        CANT_PLAY => "cant-play",
+        FROZEN_PLAYER => "frozen-player",
+        DIFFERENT_TEAM => "different-team",
+        TRY_HEAL_ITSELF => "heal-itself",
+        TRY_SUPER_ITSELF => "super-itself",
+        _ => "warning",
+    }
+}
+
+/// The outcome is decided when no player is left unfrozen and frozen players do not
+/// thaw. While a team still has a non frozen player it can freeze itself and turn its
+/// win into a tie, so nothing is reported before. A game of a single team is never reported.
+fn outcome_decided(game: &Game, teams_at_start: usize) -> bool {
+    teams_at_start > 1 && game.players.values().all(|player| player.power == 0) && !game.thaws()
+}
+
+/// Runs the scenario action by action and collects findings, ordered by line.
+/// The findings are:
+/// - actions which can only produce a warning (one category per warning)
+/// - actions performed after the outcome is decided
+/// - players from the players section which never perform an action
+///
+/// Returns Err(INVALID_INPUT) if the scenario itself is invalid
+pub fn lint(data_source: &mut impl Read) -> Result<Vec<Finding>, &'static str> {
+    let lines = BufReader::new(data_source)
+        .lines()
+        .collect::<io::Result<Vec<String>>>()
+        .map_err(|_| INVALID_INPUT)?;
+    let mut buffer = lines.iter().cloned().map(Ok);
+
+    let (teams, players) = Game::read_roster(&mut buffer)?;
+    // Player names are placed every 4 lines after the amount of players
+    let name_lines: Vec<(usize, String)> = players
+        .iter()
+        .enumerate()
+        .map(|(i, player)| (teams.len() + 3 + 4 * i, player.name.clone()))
+        .collect();
+    let teams_at_start = players
+        .iter()
+        .map(|player| player.team_number)
+        .collect::<HashSet<_>>()
+        .len();
+    let mut game = Game::new(teams, players);
+    // The iterator is left at the first action, so everything consumed is the roster
+    let roster_lines = lines.len() - buffer.len();
+
+    let mut findings = Vec::new();
+    let mut actors = HashSet::new();
+
+    for (index, line) in lines.iter().enumerate().skip(roster_lines) {
+        let action = Action::parse(line)?;
+        actors.insert(action.actor().to_string());
+
+        if outcome_decided(&game, teams_at_start) {
+            findings.push(Finding {
+                line: index + 1,
+                category: "after-decided",
+                message: "The outcome is already decided".to_string(),
+            });
+        }
+        if let Some(warning) = game.perform(&action)? {
+            findings.push(Finding {
+                line: index + 1,
+                category: warning_category(warning),
+                message: warning.to_string(),
+            });
+        }
+    }
+
+    for (line, name) in name_lines {
+        if !actors.contains(&name) {
+            findings.push(Finding {
+                line,
+                category: "idle-player",
+                message: format!("Player {} never acts", name),
+            });
+        }
+    }
+
+    findings.sort_by_key(|finding| finding.line);
+    Ok(findings)
+}
+
+/// `lint <file>...` command. Prints findings of every file and fails if any were found
+pub fn command(args: &[String]) -> Result<(), String> {
+    if args.is_empty() {
+        return Err("Usage: lint <file>...".to_string());
+    }
+
+    let mut total = 0;
+    for path in args {
+        let mut input = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        let findings = lint(&mut input).map_err(|e| format!("{}: {}", path, e))?;
+        for finding in &findings {
+            println!("{}:{}", path, finding);
+        }
+        total += findings.len();
+    }
+
+    if total > 0 {
+        return Err(format!("{} finding(s)", total));
+    }
+    Ok(())
+}
@@ -1,35 +1,81 @@
+mod effects;
+mod election;
+mod energy;
+mod engine;
+mod event;
+mod format;
+mod generate;
+mod history;
+mod json;
+mod lineage;
+mod lint;
+mod minimax;
+mod minimize;
+mod moves;
+mod multiplayer;
+mod random;
+mod ratings;
+mod rounds;
+mod rules;
+mod scenario;
+mod server;
+mod simulate;
+mod solver;
+mod strategy;
+mod tactics;
 mod test;
+mod tournament;
+mod turns;
+mod view;
 
 use std::{
-    cell::{RefCell, RefMut},
     collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader, Lines, Write},
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Write},
 };
 
-const INVALID_INPUT: &'static str = "Invalid inputs";
-const CANT_PLAY: &'static str = "This player can't play";
-const FROZEN_PLAYER: &'static str = "This player is frozen";
-const DIFFERENT_TEAM: &'static str = "Both players should be from the same team";
-const TRY_HEAL_ITSELF: &'static str = "The player cannot heal itself";
-const TRY_SUPER_ITSELF: &'static str = "The player cannot do super action with itself";
+use effects::Scheduler;
+use energy::Energy;
+use event::{Event, Outcome};
+use lineage::SplitRule;
+use rules::Rules;
+use turns::Turns;
+
+const INVALID_INPUT: &str = "Invalid inputs";
+const CANT_PLAY: &str = "This player can't play";
+const FROZEN_PLAYER: &str = "This player is frozen";
+const DIFFERENT_TEAM: &str = "Both players should be from the same team";
+const TRY_HEAL_ITSELF: &str = "The player cannot heal itself";
+const TRY_SUPER_ITSELF: &str = "The player cannot do super action with itself";
+const TRY_SUPER_TWICE: &str = "The player cannot take part in super action twice";
+const TRY_SHIELD_ITSELF: &str = "The player cannot shield itself";
+const TRY_TRANSFER_ITSELF: &str = "The player cannot transfer power to itself";
+const NOT_FROZEN: &str = "The player is not frozen";
+const NOT_ENOUGH_POWER: &str = "The player does not have enough power";
+const NOT_SUPER: &str = "The player is not a super player";
+const OUT_OF_TURN: &str = "It is not the turn of this player";
+const INSUFFICIENT_ENERGY: &str = "This player has insufficient energy";
 
 /// Structure that represents player according to the problem description
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Debug)]
 struct Player {
     name: String,
     team_number: u64,
     power: u64,
     is_visible: bool,
+    // Energy left for actions, None if actions cost nothing
+    energy: Option<u64>,
 }
 
 impl Player {
-    /// Compare Player and &RefMut<Player> by comparing every field
-    fn equal(&self, other: &RefMut<Player>) -> bool {
+    /// Compare two players by comparing every field
+    fn equal(&self, other: &Player) -> bool {
         self.name == other.name
             && self.team_number == other.team_number
             && self.power == other.power
             && self.is_visible == other.is_visible
+            && self.energy == other.energy
     }
 
     /// ## A player attacks other player
@@ -42,10 +88,10 @@ impl Player {
     /// - Err(message) (otherwise)
     /// ### Possible error messages:
     /// - CANT_PLAY
-    ///     (returned if player is invisible so they can not perform attack action)
+    ///   (returned if player is invisible so they can not perform attack action)
     /// - FROZEN_PLAYER
-    ///     (returned if player is frozen so they can not perform attack action)
-    fn attack(&mut self, other: &mut RefMut<Player>) -> Result<(), &'static str> {
+    ///   (returned if player is frozen so they can not perform attack action)
+    fn attack(&mut self, other: &mut Player) -> Result<(), &'static str> {
         if !self.is_visible {
             return Err(CANT_PLAY);
         }
@@ -81,14 +127,14 @@ impl Player {
     /// - Err(message) (otherwise)
     /// ### Possible error messages:
     /// - CANT_PLAY
-    ///     (returned if player is invisible so they can not perform healing action)
+    ///   (returned if player is invisible so they can not perform healing action)
     /// - FROZEN_PLAYER
-    ///     (returned if player is frozen so they can not perform healing action)
+    ///   (returned if player is frozen so they can not perform healing action)
     /// - DIFFERENT_TEAM
-    ///     (returned if players are from different teams)
+    ///   (returned if players are from different teams)
     /// - TRY_HEAL_ITSELF
-    ///     (returned when the action player tryes to heal itself)
-    fn heal(&mut self, other: &mut RefMut<Player>) -> Result<(), &'static str> {
+    ///   (returned when the action player tryes to heal itself)
+    fn heal(&mut self, other: &mut Player) -> Result<(), &'static str> {
         if !self.is_visible {
             return Err(CANT_PLAY);
         }
@@ -101,7 +147,7 @@ impl Player {
             return Err(DIFFERENT_TEAM);
         }
 
-        if self.equal(&other) {
+        if self.equal(other) {
             return Err(TRY_HEAL_ITSELF);
         }
 
@@ -120,8 +166,8 @@ impl Player {
     /// - Err(message) (otherwise)
     /// ### Possible error messages:
     /// - FROZEN_PLAYER
-    ///     (returned if the player is frozen so they can not perform
-    /// flip visibility action)
+    ///   (returned if the player is frozen so they can not perform
+    ///   flip visibility action)
     fn flip_visibility(&mut self) -> Result<(), &'static str> {
         if self.power == 0 {
             return Err(FROZEN_PLAYER);
@@ -134,40 +180,253 @@ impl Player {
 }
 
 /// Structure representing game
+#[derive(Clone)]
 struct Game {
     // Vector of teams
     teams: Vec<String>,
-    // It is required to use RefCell here as we need to modify players during the game
-    players: HashMap<String, RefCell<Player>>,
+    // Players by their names. Players are plain values, so the game can be
+    // sent to and shared between threads
+    players: HashMap<String, Player>,
     // Vector of warnings, generated while running the game
     warnings: Vec<&'static str>,
+    // Every played action with its outcome in the order of the actions section
+    events: Vec<Event>,
     // Counter of existing super players (needed for naming super players)
     super_player_counter: u64,
+    // Actions taken under the turn policy of the rules
+    turns: Turns,
+    // Costs of actions, if actions cost energy
+    energy: Option<Energy>,
+    // Timed effects waiting to expire
+    scheduler: Scheduler,
+    // Shielding players by the names of the players they shield
+    shields: HashMap<String, String>,
+    // Players every super player was made of, as they were when merged
+    lineage: HashMap<String, Vec<Player>>,
+    // How split super players share their power
+    split_rule: SplitRule,
 }
-/// Try to read next line from BufReader.
+
+/// Structure that represents a single line of the actions section
+#[derive(PartialEq, Clone, Debug)]
+enum Action {
+    Attack(String, String),
+    Heal(String, String),
+    FlipVisibility(String),
+    // The acting player and its partners, at least one
+    Super(String, Vec<String>),
+    Shield(String, String),
+    Revive(String, String),
+    // The amount of power is the only number in the actions section
+    Transfer(String, String, u64),
+    Sacrifice(String),
+    Split(String),
+}
+
+impl Action {
+    /// Parses an action line. Only the shape of the line is checked here,
+    /// players are looked up when the action is performed.
+    ///
+    /// Returns Ok(action) if the line is a known action with the right amount of words
+    /// and a valid amount of power for `transfer`, and Err(INVALID_INPUT) otherwise
+    fn parse(line: &str) -> Result<Self, &'static str> {
+        // Split the line into the words
+        let words = line.split(' ').collect::<Vec<&str>>();
+
+        // Match the first word and the amount of words with possible actions
+        match (words[0], words.len()) {
+            ("attack", 3) => Ok(Action::Attack(words[1].to_string(), words[2].to_string())),
+            ("heal", 3) => Ok(Action::Heal(words[1].to_string(), words[2].to_string())),
+            ("flip_visibility", 2) => Ok(Action::FlipVisibility(words[1].to_string())),
+            ("super", 3..) => Ok(Action::Super(
+                words[1].to_string(),
+                words[2..].iter().map(|word| word.to_string()).collect(),
+            )),
+            ("shield", 3) => Ok(Action::Shield(words[1].to_string(), words[2].to_string())),
+            ("revive", 3) => Ok(Action::Revive(words[1].to_string(), words[2].to_string())),
+            ("transfer", 4) => Ok(Action::Transfer(
+                words[1].to_string(),
+                words[2].to_string(),
+                Action::parse_amount(words[3])?,
+            )),
+            ("sacrifice", 2) => Ok(Action::Sacrifice(words[1].to_string())),
+            ("split", 2) => Ok(Action::Split(words[1].to_string())),
+            // Unknown command or wrong amount of words
+            _ => Err(INVALID_INPUT),
+        }
+    }
+
+    /// Parses the amount of power of `transfer`: digits only, without leading zeros,
+    /// from 1 to 1000 like a power
+    ///
+    /// Returns Err(INVALID_INPUT) if the amount is not valid
+    fn parse_amount(word: &str) -> Result<u64, &'static str> {
+        if !word.bytes().all(|symbol| symbol.is_ascii_digit()) || word.starts_with('0') {
+            return Err(INVALID_INPUT);
+        }
+        match word.parse() {
+            Ok(amount @ 1..=1000) => Ok(amount),
+            _ => Err(INVALID_INPUT),
+        }
+    }
+
+    /// Names of all players mentioned in the action, the acting player first
+    fn players(&self) -> Vec<&str> {
+        match self {
+            Action::Super(actor, partners) => [actor]
+                .into_iter()
+                .chain(partners)
+                .map(String::as_str)
+                .collect(),
+            Action::Attack(actor, target)
+            | Action::Heal(actor, target)
+            | Action::Shield(actor, target)
+            | Action::Revive(actor, target)
+            | Action::Transfer(actor, target, _) => vec![actor, target],
+            Action::FlipVisibility(actor) | Action::Sacrifice(actor) | Action::Split(actor) => {
+                vec![actor]
+            }
+        }
+    }
+
+    /// Name of the player performing the action
+    fn actor(&self) -> &str {
+        match self {
+            Action::Attack(actor, _)
+            | Action::Heal(actor, _)
+            | Action::FlipVisibility(actor)
+            | Action::Super(actor, _)
+            | Action::Shield(actor, _)
+            | Action::Revive(actor, _)
+            | Action::Transfer(actor, _, _)
+            | Action::Sacrifice(actor)
+            | Action::Split(actor) => actor,
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    /// Writes the action in the same form it has in the input file
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Attack(actor, target) => write!(f, "attack {} {}", actor, target),
+            Action::Heal(actor, target) => write!(f, "heal {} {}", actor, target),
+            Action::FlipVisibility(actor) => write!(f, "flip_visibility {}", actor),
+            Action::Super(actor, partners) => write!(f, "super {} {}", actor, partners.join(" ")),
+            Action::Shield(actor, target) => write!(f, "shield {} {}", actor, target),
+            Action::Revive(actor, target) => write!(f, "revive {} {}", actor, target),
+            Action::Transfer(actor, target, amount) => {
+                write!(f, "transfer {} {} {}", actor, target, amount)
+            }
+            Action::Sacrifice(actor) => write!(f, "sacrifice {}", actor),
+            Action::Split(actor) => write!(f, "split {}", actor),
+        }
+    }
+}
+
+/// Try to read next line from the lines iterator.
 /// If operation was successful, returns the line.
 /// Returns error INVALID_INPUT otherwise
-fn next_string(buffer: &mut Lines<BufReader<&mut File>>) -> Result<String, &'static str> {
-    Ok(buffer
+fn next_string(
+    buffer: &mut impl Iterator<Item = io::Result<String>>,
+) -> Result<String, &'static str> {
+    buffer
         .next()
         .ok_or(INVALID_INPUT)?
-        .map_err(|_| INVALID_INPUT)?)
+        .map_err(|_| INVALID_INPUT)
+}
+
+/// Lines of the input. With tolerant input, whitespace around and between words
+/// is normalized to single spaces and blank lines are skipped
+fn input_lines<'a>(
+    data_source: &'a mut impl Read,
+    rules: &Rules,
+) -> impl Iterator<Item = io::Result<String>> + 'a {
+    let tolerant = rules.tolerant_input;
+    BufReader::new(data_source)
+        .lines()
+        .map(move |line| match line {
+            Ok(line) if tolerant => Ok(line.split_whitespace().collect::<Vec<&str>>().join(" ")),
+            other => other,
+        })
+        .filter(move |line| !(tolerant && matches!(line, Ok(line) if line.is_empty())))
 }
 
 impl Game {
     /// Main logic function representing game process.
     /// Read the problem description to see the steps of the game
-    fn run(data_source: &mut File) -> Result<Self, &'static str> {
-        let mut game = Self {
-            teams: Vec::new(),
-            players: HashMap::new(),
+    fn run(data_source: &mut impl Read, rules: &Rules) -> Result<Self, &'static str> {
+        let mut buffer = input_lines(data_source, rules);
+
+        let (teams, players) = Game::read_roster(&mut buffer)?;
+        let mut game = Game::new(teams, players);
+        game.turns.policy = rules.turn_policy;
+        game.split_rule = rules.split_rule;
+        if let Some(energy) = rules.energy {
+            game.enable_energy(energy);
+        }
+        game.enable_effects(rules.effects);
+
+        // Actions of the current round, if actions are resolved in rounds
+        let mut round = Vec::new();
+        // Iterate over the rest of the lines until we meet the end the file
+        while let Ok(command_string) = next_string(&mut buffer) {
+            // Rounds are only known to the rules that need them
+            if rules.uses_rounds() && command_string == rounds::END_ROUND {
+                if rules.simultaneous_rounds {
+                    game.play_round(&round)?;
+                    round.clear();
+                } else {
+                    game.close_round();
+                }
+                continue;
+            }
+
+            // Parse the line and perform it, invalid lines stop the whole game
+            let action = Action::parse(&command_string)?;
+            if rules.simultaneous_rounds {
+                round.push(action);
+            } else {
+                game.play(&action)?;
+            }
+        }
+        // The last round does not need the marker
+        if !round.is_empty() {
+            game.play_round(&round)?;
+        }
+        Ok(game)
+    }
+
+    /// Creates a game before any action was performed
+    fn new(teams: Vec<String>, players: Vec<Player>) -> Self {
+        Self {
+            teams,
+            players: players
+                .into_iter()
+                .map(|player| (player.name.clone(), player))
+                .collect(),
             warnings: Vec::new(),
+            events: Vec::new(),
             super_player_counter: 0,
-        };
+            turns: Turns::default(),
+            energy: None,
+            scheduler: Scheduler::default(),
+            shields: HashMap::new(),
+            lineage: HashMap::new(),
+            split_rule: SplitRule::default(),
+        }
+    }
 
-        let mut buffer = BufReader::new(data_source).lines();
+    /// Reads the teams and the players sections of the input.
+    /// Players are returned in the order of the input.
+    /// The buffer is left at the first line of the actions section
+    fn read_roster(
+        buffer: &mut impl Iterator<Item = io::Result<String>>,
+    ) -> Result<(Vec<String>, Vec<Player>), &'static str> {
+        let mut teams = Vec::new();
+        let mut players = Vec::new();
 
-        let total_teams: u64 = next_string(&mut buffer)? // Get line with number N and return error if reading failed
+        let total_teams: u64 = next_string(buffer)? // Get line with number N and return error if reading failed
             .parse() // Try convert the value into the u64 value
             .map_or_else(
                 |_| Err(INVALID_INPUT), // If parsing fails, map parse error to INVALID_INPUT
@@ -177,16 +436,16 @@ impl Game {
 
         // Get team names from the file, or report
         for _ in 0..total_teams {
-            game.teams.push(
+            teams.push(
                 // Validate name
                 Game::validate_name(
-                    next_string(&mut buffer)?, // Get line with team name. Return error if reading failed
+                    next_string(buffer)?, // Get line with team name. Return error if reading failed
                 )?, // Return Ok(&name) if the name is valid and error INVALID_INPUT otherwise
             )
         }
 
         // Get total amount of players
-        let total_playes: u64 = next_string(&mut buffer)? // Reads the next line and returns error if reading failed
+        let total_playes: u64 = next_string(buffer)? // Reads the next line and returns error if reading failed
             .parse() // Try convert the line into the u64 value
             .map_or_else(
                 |_| Err(INVALID_INPUT), // If parsing failed, map parse error into INVALID_INPUT error
@@ -197,17 +456,17 @@ impl Game {
         for _ in 0..total_playes {
             let name: String = Game::validate_name(
                 // Validate name
-                next_string(&mut buffer)?, // Reads the next line and returns error if reading failed
+                next_string(buffer)?, // Reads the next line and returns error if reading failed
             )?; // Return name if it is valid and return error INVALID_INPUT otherwise
 
-            let team_number: u64 = next_string(&mut buffer)? // Read next line and return error if readig failed
+            let team_number: u64 = next_string(buffer)? // Read next line and return error if readig failed
                 .parse() // Try convert the line into the u64 value
                 .map_or_else(
                     |_| Err(INVALID_INPUT), // If parsing failed, covert parse error into INVALID_INPUT error
                     |tn| Game::validate_team_number(total_teams, tn), // Validate the number otherwise
                 )?; // Returns u64 if the value is valid and returns error INVALID_INPUT otherwise
 
-            let power: u64 = next_string(&mut buffer)? // Reads the next line and returns error if reading failed
+            let power: u64 = next_string(buffer)? // Reads the next line and returns error if reading failed
                 .parse() // Try convert the line into the u64 value
                 .map_or_else(
                     |_| Err(INVALID_INPUT), // If parsing failed, convert parse error into INVALID_INPUT error
@@ -215,167 +474,193 @@ impl Game {
                 )?; // Returns u64 value if power is valid and returns error INVALID_INPUT otherwise
 
             let is_visible: bool = Game::validate_visibility(
-                next_string(&mut buffer)?, // Reads the next line and returns error if reading failed
+                next_string(buffer)?, // Reads the next line and returns error if reading failed
             )?; // Return bool value if visibility is valid and returns error INVALID_INPUT otherwise
 
-            // Save new player into the players hashmap
-            game.players.insert(
-                name.clone(), // We need to clone this string
-                RefCell::new(Player {
-                    name: name, // As value moved here into the structure
-                    team_number,
-                    power,
-                    is_visible,
-                }),
-            );
-        }
-
-        loop {
-            // Iterate over the rest of the lines until we meet the end the file
-            let command_string = match next_string(&mut buffer) {
-                Ok(string) => string,
-                Err(_) => break,
-            };
-            // Split the line into the words
-            let words = command_string.split(" ").collect::<Vec<&str>>();
-            // All commands contain between 2 and 3 words, so
-            // any other amount of words is invalid command
-            if words.len() < 2 || words.len() >= 4 {
-                return Err(INVALID_INPUT);
+            // Save new player
+            players.push(Player {
+                name,
+                team_number,
+                power,
+                is_visible,
+                energy: None,
+            });
+        }
+
+        Ok((teams, players))
+    }
+
+    /// Performs the action as a step of the game: keeps its warning and
+    /// writes the action with its outcome to the event log
+    ///
+    /// Returns Err(INVALID_INPUT) if the action refers to a player that does not exist
+    fn play(&mut self, action: &Action) -> Result<(), &'static str> {
+        let super_player_counter = self.super_player_counter;
+        let affected = self.affected(action);
+        let involved = self.involved(action);
+        let mut changed = Vec::new();
+        let outcome = match self.perform(action)? {
+            Some(warning) => {
+                self.warnings.push(warning);
+                Outcome::Warning(warning)
             }
+            None => {
+                let names: Vec<String> = if self.super_player_counter > super_player_counter {
+                    // Merged players are gone, only the super player is left
+                    vec![format!("S_{}", super_player_counter)]
+                } else {
+                    affected
+                };
+                changed = names.clone();
+                Outcome::Changed(
+                    names
+                        .iter()
+                        .map(|name| self.players[name].clone())
+                        .collect(),
+                )
+            }
+        };
+        self.events.push(Event {
+            action: Some(action.clone()),
+            outcome,
+            involved,
+        });
+        self.tick_actions(1, &changed);
+        Ok(())
+    }
 
-            // Match the first word with possible actions
-            match words[0] {
-                "attack" => {
-                    // If action is "attack", the amount of words should be equal to 3
-                    if words.len() != 3 {
-                        return Err(INVALID_INPUT);
-                    }
-                    let mut action_player = game
-                        .players
-                        .get(words[1]) // Try find the 1st player
-                        .ok_or(INVALID_INPUT)? // return reference to a player or raise INVALID_INPUT error
-                        .borrow_mut(); // borrow as mutable, because we need to modify it during the attack action
-                    let mut target_player = game
-                        .players
-                        .get(words[2]) // Try find the 2nd player
-                        .ok_or(INVALID_INPUT)? // return reference to a player or raise INVALID_INPUT error
-                        .borrow_mut(); // borrow as mutable, because we need to modify it during the attack action
-
-                    // Perform the attack
-                    // If any errors occur, add then as warnings
-                    if let Err(e) = action_player.attack(&mut target_player) {
-                        game.warnings.push(e);
-                    }
-                }
-                "heal" => {
-                    // If the action is "heal", the amount of words should be 3
-                    if words.len() != 3 {
-                        return Err(INVALID_INPUT);
-                    }
-
-                    let mut action_player = game
-                        .players
-                        .get(words[1]) // Try find the 1st player
-                        .ok_or(INVALID_INPUT)? // return reference to a player or raise INVALID_INPUT error
-                        .borrow_mut(); // borrow as mutable, because we need to modify it during the heal action
-
-                    let mut target_player = game
-                        .players
-                        .get(words[2]) // Try find the 2nd player
-                        .ok_or(INVALID_INPUT)? // return reference to a player or raise INVALID_INPUT error
-                        .borrow_mut(); // borrow as mutable, because we need to modify it during the heal action
-
-                    // Perform heal action and if any errors occur save them to the vector of warnings
-                    if let Err(e) = action_player.heal(&mut target_player) {
-                        game.warnings.push(e)
-                    }
-                }
-                "flip_visibility" => {
-                    // If the action is "flip_visibility" the amount of words should be equal 2
-                    if words.len() != 2 {
-                        return Err(INVALID_INPUT);
-                    }
-
-                    // Perform flip_visibility action
-                    // and if any errors occur, save them to the vector of warnings
-                    if let Err(e) = game
-                        .players
-                        .get_mut(words[1])
-                        .ok_or(INVALID_INPUT)?
-                        .get_mut()
-                        .flip_visibility()
-                    {
-                        game.warnings.push(e)
-                    }
-                }
-                "super" => {
-                    // Super action can not be performed inside player actions,
-                    // as this action modify global game state (amount of players)
-
-                    // If the action is "super", the amount of words should be equal to 3
-                    if words.len() != 3 {
-                        return Err(INVALID_INPUT);
-                    }
-
-                    // Refcell is needed to be cloned because we are going to modify several objects from
-                    // the hashmap at the same time
-                    // If there is no such player, raise INVALID_INPUT error
-                    let _action_cell = game.players.get(words[1]).cloned().ok_or(INVALID_INPUT)?;
-                    // _action_cell should be declared, or the value will be dropped in this scope
-                    // player will not be modifed so borrow as immutable
-                    let action_player = _action_cell.borrow();
-
-                    let _target_cell = game.players.get(words[2]).cloned().ok_or(INVALID_INPUT)?;
-                    let target_player = _target_cell.borrow();
-
-                    // Invisible player can not perform any actions other then flip_visibility
-                    if !action_player.is_visible {
-                        game.warnings.push(CANT_PLAY);
-                        continue;
-                    }
-                    // Frozen player can not perform any actions
-                    if action_player.power == 0 {
-                        game.warnings.push(FROZEN_PLAYER);
-                        continue;
-                    }
-
-                    // Players should be from the same team
-                    if action_player.team_number != target_player.team_number {
-                        game.warnings.push(DIFFERENT_TEAM);
-                        continue;
-                    }
-
-                    // Super actions with itself is prohibited
-                    // names of players are unique by the task, so we can use them
-                    if words[1] == words[2] {
-                        game.warnings.push(TRY_SUPER_ITSELF);
-                        continue;
-                    }
-
-                    // Remove both players from the players hashmap
-                    game.players.remove(words[1]);
-                    game.players.remove(words[2]);
-
-                    // create a new player and save it to the players of the game
-                    let super_player_name = format!("S_{}", game.super_player_counter);
-                    game.super_player_counter += 1;
-
-                    game.players.insert(
-                        super_player_name.clone(),
-                        RefCell::new(Player {
-                            name: super_player_name,
-                            power: 1000.min(action_player.power + target_player.power),
-                            is_visible: true,
-                            team_number: action_player.team_number,
-                        }),
-                    );
+    /// Performs a single action on the current state of the game.
+    /// ### Returns:
+    /// - Ok(None) (if the action was performed)
+    /// - Ok(Some(warning)) (if the action was ignored, see the warnings order in the task)
+    /// - Err(INVALID_INPUT) (if the action refers to a player that does not exist)
+    fn perform(&mut self, action: &Action) -> Result<Option<&'static str>, &'static str> {
+        if let Some(warning) = self.warning_of(action).ok_or(INVALID_INPUT)? {
+            return Ok(Some(warning));
+        }
+        self.pay_energy(action);
+        // The actor of a super action is gone after it
+        let team = self
+            .players
+            .get(action.actor())
+            .map(|player| player.team_number);
+
+        let result = match action {
+            Action::Attack(actor, target) => {
+                // A shielded target is replaced by its shield, the shield is used up
+                // only if the attack happens
+                let shield = self.shield_for(action);
+                let result =
+                    self.interact(actor, shield.as_deref().unwrap_or(target), Player::attack)?;
+                if result.is_ok() && shield.is_some() {
+                    self.shields.remove(target);
                 }
-                // If unknown command is met, raise INVALID_INPUT error
-                _ => return Err(INVALID_INPUT),
+                result
+            }
+            Action::Heal(actor, target) => {
+                // Perform heal action
+                self.interact(actor, target, Player::heal)?
             }
+            Action::FlipVisibility(actor) => {
+                // Perform flip_visibility action
+                self.players
+                    .get_mut(actor)
+                    .ok_or(INVALID_INPUT)?
+                    .flip_visibility()
+            }
+            Action::Super(actor, partners) => {
+                // Super action can not be performed inside player actions,
+                // as this action modify global game state (amount of players)
+                self.merge(actor, partners)?.map_or(Ok(()), Err)
+            }
+            Action::Shield(_, _)
+            | Action::Revive(_, _)
+            | Action::Transfer(_, _, _)
+            | Action::Sacrifice(_)
+            | Action::Split(_) => self.perform_tactic(action)?.map_or(Ok(()), Err),
+        };
+
+        if let (Ok(()), Some(team)) = (result, team) {
+            self.take_turn(team, action.actor());
+        }
+        // Errors of player actions are warnings, they do not stop the game
+        Ok(result.err())
+    }
+
+    /// Applies an action of the actor to the target, both players are modified at once
+    ///
+    /// Returns the result of the action, or Err(INVALID_INPUT) if a player does not exist
+    fn interact(
+        &mut self,
+        actor: &str,
+        target: &str,
+        action: fn(&mut Player, &mut Player) -> Result<(), &'static str>,
+    ) -> Result<Result<(), &'static str>, &'static str> {
+        if actor == target {
+            // A player can not be borrowed twice, so it acts on a copy of itself.
+            // Both attack and heal end the same way with the copy, so it is not needed afterwards
+            let action_player = self.players.get_mut(actor).ok_or(INVALID_INPUT)?;
+            let mut target_player = action_player.clone();
+            return Ok(action(action_player, &mut target_player));
+        }
+
+        match self.players.get_disjoint_mut([actor, target]) {
+            [Some(action_player), Some(target_player)] => Ok(action(action_player, target_player)),
+            // If there is no such player, raise INVALID_INPUT error
+            _ => Err(INVALID_INPUT),
         }
-        Ok(game)
+    }
+
+    /// Performs super action: replaces the acting player and its partners
+    /// from the same team with a single super player
+    ///
+    /// Returns the same values as `perform`
+    fn merge(
+        &mut self,
+        actor: &str,
+        partners: &[String],
+    ) -> Result<Option<&'static str>, &'static str> {
+        // Players are copied, because all of them are removed from the hashmap below.
+        // If there is no such player, raise INVALID_INPUT error
+        let action_player = self.players.get(actor).cloned().ok_or(INVALID_INPUT)?;
+        let partner_players = partners
+            .iter()
+            .map(|name| self.players.get(name).cloned().ok_or(INVALID_INPUT))
+            .collect::<Result<Vec<Player>, _>>()?;
+
+        // Invisible player can not perform any actions other then flip_visibility
+        if !action_player.is_visible {
+            return Ok(Some(CANT_PLAY));
+        }
+        // Frozen player can not perform any actions
+        if action_player.power == 0 {
+            return Ok(Some(FROZEN_PLAYER));
+        }
+
+        // Players should be from the same team
+        if partner_players
+            .iter()
+            .any(|partner| partner.team_number != action_player.team_number)
+        {
+            return Ok(Some(DIFFERENT_TEAM));
+        }
+
+        // Super actions with itself is prohibited
+        // names of players are unique by the task, so we can use them
+        if partners.iter().any(|partner| partner == actor) {
+            return Ok(Some(TRY_SUPER_ITSELF));
+        }
+        // Every partner takes part once
+        if (1..partners.len()).any(|index| partners[..index].contains(&partners[index])) {
+            return Ok(Some(TRY_SUPER_TWICE));
+        }
+
+        // Remove the players from the players hashmap and create a new player instead
+        let mut parts = vec![action_player];
+        parts.extend(partner_players);
+        self.fuse(parts);
+
+        Ok(None)
     }
 
     /// Validates total amount of teams. By the task, the number of teams
@@ -389,7 +674,7 @@ impl Game {
         }
     }
 
-    // Validates total amount of players. By the task, the number of players
+    /// Validates total amount of players. By the task, the number of players
     // M should be in range \[N, 100\];
     ///
     /// Returns Ok(value) if all conditions are satisfied and Err(INVALID_INPUT) otherwise
@@ -463,21 +748,33 @@ impl Game {
         }
     }
 
-    /// Finds the winner team. The winner team is a team with the largest
-    /// sum of the players' powers. If top two or more teams has equal powers
-    /// return is undefined, function returns None.
-    fn get_winner_index<'a>(&self) -> Option<usize> {
-        // Create array for calculating total scores
-        let mut team_powers: Vec<u64> = Vec::with_capacity(self.teams.len());
-        for _ in 0..self.teams.len() {
-            team_powers.push(0)
+    /// Final message of the game: the chosen wizard or a tie
+    fn verdict(&self) -> String {
+        match self.get_winner_index() {
+            // Print the chosen wizard, if it was found
+            Some(winner) => format!("The chosen wizard is {}", &self.teams[winner]),
+            // Print `it's a tie` otherwise
+            None => "It's a tie".to_string(),
         }
+    }
+
+    /// Total power of every team: the sum of its players' powers
+    fn team_powers(&self) -> Vec<u64> {
+        // Create array for calculating total scores
+        let mut team_powers: Vec<u64> = vec![0; self.teams.len()];
         // Iterate over every players
-        for player_cell in self.players.values() {
-            let player = player_cell.borrow();
+        for player in self.players.values() {
             // Add player's power to its team
             team_powers[player.team_number as usize] += player.power;
         }
+        team_powers
+    }
+
+    /// Finds the winner team. The winner team is a team with the largest
+    /// sum of the players' powers. If top two or more teams has equal powers
+    /// return is undefined, function returns None.
+    fn get_winner_index(&self) -> Option<usize> {
+        let team_powers = self.team_powers();
 
         // Find maximum value
         let max_power = team_powers.iter().max().unwrap_or(&0u64);
@@ -500,15 +797,26 @@ impl Game {
 }
 
 fn solution(input_file: &str, output_file: &str) {
+    solution_with(input_file, output_file, &Rules::default())
+}
+
+/// Same as `solution`, but plays by the given rules
+fn solution_with(input_file: &str, output_file: &str, rules: &Rules) {
     // Open input file for reading and output file for writing
     let mut input = File::open(input_file).expect("File does not exist");
     let mut output = File::create(output_file).expect("Failed to create output file");
 
-    let game = match Game::run(&mut input) {
+    write_output(&mut output, &Game::run(&mut input, rules)).unwrap();
+
+    // Files are automaticly closed here, while being destroyed
+}
+
+/// Writes the result of a game in the format of the output file
+fn write_output(output: &mut impl Write, result: &Result<Game, &'static str>) -> io::Result<()> {
+    let game = match result {
         Err(e) => {
             // If we catched error during game process report only the error
-            writeln!(&mut output, "{}", e).unwrap();
-            return;
+            return writeln!(output, "{}", e);
         }
         // Unpack game object otherwise
         Ok(g) => g,
@@ -516,25 +824,81 @@ fn solution(input_file: &str, output_file: &str) {
 
     for warning in &game.warnings {
         // Report all warnings
-        writeln!(&mut output, "{}", warning).unwrap();
+        writeln!(output, "{}", warning)?;
     }
 
     // Write final score to the file
-    writeln!(
-        &mut output,
-        "{}",
-        match game.get_winner_index() {
-            // Print the chosen wizard, if it was found
-            Some(winner) => format!("The chosen wizard is {}", &game.teams[winner]),
-            // Print `it's a tie` otherwise
-            None => "It's a tie".to_string(),
-        }
-    )
-    .unwrap();
+    writeln!(output, "{}", game.verdict())
+}
 
-    // Files are automaticly closed here, while being destroyed
+/// `play [rules...] [--archive[=FILE]] <input> <output>` command. Plays like
+/// the default mode, but with the given files and rules. With `--archive` the match
+/// is also appended to the archive, a failure to archive is only reported
+fn play_command(args: &[String]) -> Result<(), String> {
+    let archive = args.iter().find_map(|arg| match arg.as_str() {
+        "--archive" => Some(history::HISTORY_FILE),
+        arg => arg.strip_prefix("--archive="),
+    });
+    let args: Vec<String> = args
+        .iter()
+        .filter(|arg| *arg != "--archive" && !arg.starts_with("--archive="))
+        .cloned()
+        .collect();
+    let (rules, files) = Rules::from_args(&args)?;
+    let [input_file, output_file] = files.as_slice() else {
+        return Err(format!(
+            "Usage: play {} [--archive[=FILE]] <input> <output>",
+            Rules::USAGE
+        ));
+    };
+    let Some(archive) = archive else {
+        solution_with(input_file, output_file, &rules);
+        return Ok(());
+    };
+
+    // The match is played once, for the output file and for the archive
+    let input = fs::read(input_file).expect("File does not exist");
+    let mut output = File::create(output_file).expect("Failed to create output file");
+    let result = Game::run(&mut input.as_slice(), &rules);
+    write_output(&mut output, &result).unwrap();
+    if let Err(e) = history::archive(archive, &input, &result) {
+        eprintln!("{}: {}", archive, e);
+    }
+    Ok(())
 }
 
 fn main() {
-    solution("input.txt", "output.txt");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        // Without a command, play the game as the task describes
+        None => {
+            solution("input.txt", "output.txt");
+            Ok(())
+        }
+        Some("play") => play_command(&args[1..]),
+        Some("lint") => lint::command(&args[1..]),
+        Some("fmt") => format::command(&args[1..]),
+        Some("minimize") => minimize::command(&args[1..]),
+        Some("generate") => generate::command(&args[1..]),
+        Some("simulate") => simulate::command(&args[1..]),
+        Some("moves") => moves::command(&args[1..]),
+        Some("bots") => strategy::command(&args[1..]),
+        Some("solve") => solver::command(&args[1..]),
+        Some("minimax") => minimax::command(&args[1..]),
+        Some("tournament") => tournament::command(&args[1..]),
+        Some("ratings") => ratings::command(&args[1..]),
+        Some("history") => history::command(&args[1..]),
+        Some("elect") => election::command(&args[1..]),
+        Some("serve") => server::command(&args[1..]),
+        Some("engine") => engine::command(&args[1..]),
+        Some("referee") => engine::referee_command(&args[1..]),
+        Some("host") => multiplayer::command(&args[1..]),
+        Some(other) => Err(format!("Unknown command `{}`", other)),
+    };
+
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
 }
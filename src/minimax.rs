@@ -0,0 +1,121 @@
+use std::fs::File;
+
+use crate::{rules::Rules, strategy::team_actions, Action, Game};
+
+/// Result of a search: the evaluation from the point of view of the team to move
+/// and the principal variation (None is a pass of a team without legal actions)
+#[derive(PartialEq, Clone, Debug)]
+pub struct Line {
+    pub evaluation: i64,
+    pub variation: Vec<Option<Action>>,
+}
+
+/// Power of the team minus the power of the opponent
+fn evaluate(game: &Game, team: usize, opponent: usize) -> i64 {
+    let powers = game.team_powers();
+    powers[team] as i64 - powers[opponent] as i64
+}
+
+/// Negamax search with alpha-beta pruning. The team to move maximizes its margin,
+/// which is the same as minimizing the margin of the opponent
+fn negamax(
+    game: &Game,
+    team: usize,
+    opponent: usize,
+    depth: usize,
+    mut alpha: i64,
+    beta: i64,
+) -> Line {
+    if depth == 0 {
+        return Line {
+            evaluation: evaluate(game, team, opponent),
+            variation: Vec::new(),
+        };
+    }
+
+    let mut children: Vec<(Option<Action>, Game)> = team_actions(game, team)
+        .into_iter()
+        .map(|action| {
+            let mut next = game.clone();
+            next.perform(&action).unwrap();
+            (Some(action), next)
+        })
+        .collect();
+    if children.is_empty() {
+        // Without legal actions the team passes its turn
+        children.push((None, game.clone()));
+    }
+    // Searching the immediately best actions first makes cutoffs happen earlier
+    children.sort_by_key(|(_, next)| -evaluate(next, team, opponent));
+
+    let mut best = Line {
+        evaluation: i64::MIN,
+        variation: Vec::new(),
+    };
+    for (action, next) in children {
+        let reply = negamax(&next, opponent, team, depth - 1, -beta, -alpha);
+        if -reply.evaluation > best.evaluation {
+            best.evaluation = -reply.evaluation;
+            best.variation = [action].into_iter().chain(reply.variation).collect();
+        }
+        alpha = alpha.max(best.evaluation);
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+/// Searches `depth` actions ahead, the teams alternate starting from `team`.
+/// Other teams do not act
+///
+/// Returns the evaluation for `team` and the principal variation
+pub fn minimax(game: &Game, team: usize, opponent: usize, depth: usize) -> Line {
+    // Bounds are kept away from i64::MIN, so they can be negated
+    negamax(game, team, opponent, depth, -i64::MAX, i64::MAX)
+}
+
+/// `minimax <file> <depth> [--teams <first>,<second>]` command. Plays the file (a roster
+/// or a checkpoint with actions) and searches from the resulting state. By default
+/// the first two teams play, the first one moves first
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage = "Usage: minimax <file> <depth> [--teams <first magician>,<second magician>]";
+    let (path, depth, teams) = match args {
+        [path, depth] => (path, depth, None),
+        [path, depth, flag, teams] if flag == "--teams" => (path, depth, Some(teams)),
+        _ => return Err(usage.to_string()),
+    };
+    let depth: usize = depth.parse().map_err(|_| usage)?;
+
+    let mut input = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let game = Game::run(&mut input, &Rules::default()).map_err(|e| format!("{}: {}", path, e))?;
+
+    let (team, opponent) = match teams {
+        None if game.teams.len() >= 2 => (0, 1),
+        None => return Err("The game has a single team".to_string()),
+        Some(teams) => {
+            let position = |magician: &str| {
+                game.teams
+                    .iter()
+                    .position(|name| name == magician)
+                    .ok_or_else(|| format!("Unknown magician `{}`", magician))
+            };
+            let (first, second) = teams.split_once(',').ok_or(usage)?;
+            (position(first)?, position(second)?)
+        }
+    };
+    if team == opponent {
+        return Err("A team can not play against itself".to_string());
+    }
+
+    let line = minimax(&game, team, opponent, depth);
+    println!("Evaluation for {}: {}", game.teams[team], line.evaluation);
+    for (ply, action) in line.variation.iter().enumerate() {
+        let mover = &game.teams[if ply % 2 == 0 { team } else { opponent }];
+        match action {
+            Some(action) => println!("{}: {}", mover, action),
+            None => println!("{}: pass", mover),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,240 @@
+use std::{fs, io, iter};
+
+use crate::{
+    next_string, rules::Rules, scenario::Scenario, write_output, Action, Game, INVALID_INPUT,
+};
+
+/// Property of the output which should be kept while the scenario is minimized
+#[derive(PartialEq, Clone, Debug)]
+pub enum Predicate {
+    // Output contains the text
+    Contains(String),
+    // Last line of the output is the verdict: a tie or the chosen wizard
+    Verdict(String),
+    // The whole input is rejected with INVALID_INPUT
+    Rejected,
+}
+
+impl Predicate {
+    /// Checks the predicate against the output of a scenario
+    fn holds(&self, text: &str) -> bool {
+        let output = output_of(text);
+        match self {
+            Predicate::Contains(expected) => output.contains(expected.as_str()),
+            Predicate::Verdict(verdict) => output.lines().last() == Some(verdict.as_str()),
+            Predicate::Rejected => output.trim_end() == INVALID_INPUT,
+        }
+    }
+}
+
+/// Output the game would produce for the given input
+fn output_of(text: &str) -> String {
+    let mut output = Vec::new();
+    write_output(
+        &mut output,
+        &Game::run(&mut text.as_bytes(), &Rules::default()),
+    )
+    .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+/// Finds the line because of which the input is rejected.
+/// ### Returns:
+/// - None (if the input is accepted)
+/// - Some("") (if the input ends before the roster is complete)
+/// - Some(line) (if the line is malformed or refers to a missing player)
+fn offending_line(text: &str) -> Option<String> {
+    let mut current = None;
+    let outcome = {
+        // The extra None marks reading past the end of the input
+        let mut buffer = text.lines().map(Some).chain(iter::once(None)).map(|line| {
+            current = line;
+            line.map(String::from)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "end of input"))
+        });
+
+        Game::read_roster(&mut buffer).and_then(|(teams, players)| {
+            let mut game = Game::new(teams, players);
+            while let Ok(line) = next_string(&mut buffer) {
+                game.perform(&Action::parse(&line)?)?;
+            }
+            Ok(())
+        })
+    };
+
+    outcome
+        .err()
+        .map(|_| current.unwrap_or_default().to_string())
+}
+
+/// Delta debugging (ddmin): finds a subsequence of items, for which the test still holds,
+/// such that removing any single chunk of it on the finest granularity breaks the test
+fn ddmin<T: Clone>(mut items: Vec<T>, test: &mut impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut chunks = 2;
+    while items.len() >= 2 {
+        let chunk_size = items.len().div_ceil(chunks);
+        let mut reduced = false;
+
+        for start in (0..items.len()).step_by(chunk_size) {
+            // Try the complement of the chunk first, it removes the chunk itself
+            let complement: Vec<T> = items[..start]
+                .iter()
+                .chain(items[(start + chunk_size).min(items.len())..].iter())
+                .cloned()
+                .collect();
+            if test(&complement) {
+                items = complement;
+                chunks = 2.max(chunks - 1);
+                reduced = true;
+                break;
+            }
+        }
+
+        if !reduced {
+            if chunks >= items.len() {
+                break;
+            }
+            chunks = items.len().min(chunks * 2);
+        }
+    }
+
+    // A single remaining item can still be unnecessary
+    if items.len() == 1 && test(&[]) {
+        items.clear();
+    }
+    items
+}
+
+/// Checks the counts of the roster, so candidates never fail only because of them
+fn roster_is_valid(scenario: &Scenario) -> bool {
+    !scenario.teams.is_empty()
+        && scenario.teams.len() <= 10
+        && scenario.teams.len() <= scenario.players.len()
+        && scenario.players.len() <= 100
+}
+
+/// Scenario without the team: its players and all actions mentioning them are removed,
+/// teams after it are renumbered
+pub fn without_team(scenario: &Scenario, team: usize) -> Scenario {
+    let mut candidate = scenario.clone();
+    candidate.teams.remove(team);
+
+    let removed: Vec<String> = scenario
+        .players
+        .iter()
+        .filter(|player| player.team_number == team as u64)
+        .map(|player| player.name.clone())
+        .collect();
+    candidate
+        .players
+        .retain(|player| player.team_number != team as u64);
+    for player in candidate.players.iter_mut() {
+        if player.team_number > team as u64 {
+            player.team_number -= 1;
+        }
+    }
+    candidate.actions.retain(|action| {
+        action
+            .players()
+            .iter()
+            .all(|name| !removed.iter().any(|removed| removed == name))
+    });
+    candidate
+}
+
+/// Scenario without the player and all actions mentioning it
+fn without_player(scenario: &Scenario, player: usize) -> Scenario {
+    let mut candidate = scenario.clone();
+    let removed = candidate.players.remove(player);
+    candidate
+        .actions
+        .retain(|action| !action.players().contains(&removed.name.as_str()));
+    candidate
+}
+
+/// Minimizes a parsed scenario: removes teams, then players, then actions,
+/// until none of them can be removed without breaking the test
+fn minimize_scenario(mut scenario: Scenario, test: &impl Fn(&str) -> bool) -> Scenario {
+    let holds = |candidate: &Scenario| roster_is_valid(candidate) && test(&candidate.to_string());
+
+    loop {
+        let before = scenario.clone();
+
+        // Remove teams one by one, the last ones first so indices stay valid
+        for team in (0..scenario.teams.len()).rev() {
+            let candidate = without_team(&scenario, team);
+            if holds(&candidate) {
+                scenario = candidate;
+            }
+        }
+
+        // Remove players one by one
+        for player in (0..scenario.players.len()).rev() {
+            let candidate = without_player(&scenario, player);
+            if holds(&candidate) {
+                scenario = candidate;
+            }
+        }
+
+        // Remove actions with delta debugging
+        let mut candidate = scenario.clone();
+        scenario.actions = ddmin(scenario.actions.clone(), &mut |actions| {
+            candidate.actions = actions.to_vec();
+            holds(&candidate)
+        });
+
+        if scenario == before {
+            return scenario;
+        }
+    }
+}
+
+/// Minimizes any input. Inputs which can not even be parsed are minimized line by line.
+/// A rejected input stays rejected because of the same line, otherwise an empty file
+/// would always be the answer
+///
+/// Returns Err(message) if the predicate does not hold for the input itself
+pub fn minimize(text: &str, predicate: &Predicate) -> Result<String, String> {
+    let offending = offending_line(text);
+    let test = |candidate: &str| match predicate {
+        Predicate::Rejected => offending.is_some() && offending_line(candidate) == offending,
+        _ => predicate.holds(candidate),
+    };
+
+    if !test(text) {
+        return Err("The predicate does not hold for the input".to_string());
+    }
+
+    match Scenario::read(&mut text.as_bytes(), &Rules::default()) {
+        Ok(scenario) => Ok(minimize_scenario(scenario, &test).to_string()),
+        Err(_) => {
+            let as_text = |lines: &[&str]| -> String {
+                lines.iter().map(|line| format!("{}\n", line)).collect()
+            };
+            let lines = ddmin(text.lines().collect(), &mut |lines| test(&as_text(lines)));
+            Ok(as_text(&lines))
+        }
+    }
+}
+
+/// `minimize <file> (--contains <text> | --verdict <tie|magician> | --rejected)` command.
+/// Prints the smallest found input for which the predicate still holds
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage =
+        "Usage: minimize <file> (--contains <text> | --verdict <tie|magician> | --rejected)";
+
+    let predicate = match args {
+        [_, flag] if flag == "--rejected" => Predicate::Rejected,
+        [_, flag, text] if flag == "--contains" => Predicate::Contains(text.clone()),
+        [_, flag, verdict] if flag == "--verdict" => Predicate::Verdict(if verdict == "tie" {
+            "It's a tie".to_string()
+        } else {
+            format!("The chosen wizard is {}", verdict)
+        }),
+        _ => return Err(usage.to_string()),
+    };
+
+    let text = fs::read_to_string(&args[0]).map_err(|e| format!("{}: {}", args[0], e))?;
+    print!("{}", minimize(&text, &predicate)?);
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use std::fs::File;
+
+use crate::{
+    rules::Rules, tactics::REVIVE_COST, Action, Game, CANT_PLAY, DIFFERENT_TEAM, FROZEN_PLAYER,
+    INSUFFICIENT_ENERGY, NOT_ENOUGH_POWER, NOT_FROZEN, NOT_SUPER, OUT_OF_TURN, TRY_HEAL_ITSELF,
+    TRY_SHIELD_ITSELF, TRY_SUPER_ITSELF, TRY_SUPER_TWICE, TRY_TRANSFER_ITSELF,
+};
+
+/// Most partners of a super action enumerated by `Game::classify_actions`.
+/// A super action then loses at most the power of two players to the cap of 1000
+pub const MAX_PARTNERS: usize = 2;
+
+impl Game {
+    /// Finds the warning the action would produce, without performing it. This is the
+    /// only place deciding the order of the warnings, `perform` and `play_round` use it:
+    /// 1. CANT_PLAY and FROZEN_PLAYER
+    /// 2. OUT_OF_TURN
+    /// 3. the other warnings, in the order given in the task
+    /// 4. INSUFFICIENT_ENERGY
+    ///
+    /// Returns None for actions with players that do not exist
+    pub fn warning_of(&self, action: &Action) -> Option<Option<&'static str>> {
+        let warning = self.rule_warning(action)?;
+        let warning = match warning {
+            Some(CANT_PLAY | FROZEN_PLAYER) => warning,
+            _ if self.out_of_turn(action) => Some(OUT_OF_TURN),
+            Some(_) => warning,
+            None => self.lacks_energy(action).then_some(INSUFFICIENT_ENERGY),
+        };
+        Some(warning)
+    }
+
+    /// Finds the warning of the rules of the actions, whatever the turn and the energy.
+    /// Warnings are checked in the order given in the task
+    ///
+    /// Returns None for actions with players that do not exist
+    pub fn rule_warning(&self, action: &Action) -> Option<Option<&'static str>> {
+        let players = action
+            .players()
+            .iter()
+            .map(|name| self.players.get(*name).cloned())
+            .collect::<Option<Vec<_>>>()?;
+        let actor = &players[0];
+
+        let warning = match action {
+            Action::FlipVisibility(_) => (actor.power == 0).then_some(FROZEN_PLAYER),
+            _ if !actor.is_visible => Some(CANT_PLAY),
+            _ if actor.power == 0 => Some(FROZEN_PLAYER),
+            Action::Attack(_, _) | Action::Sacrifice(_) => None,
+            Action::Split(_) => (!self.lineage.contains_key(&actor.name)).then_some(NOT_SUPER),
+            _ if players[1..]
+                .iter()
+                .any(|player| player.team_number != actor.team_number) =>
+            {
+                Some(DIFFERENT_TEAM)
+            }
+            Action::Heal(_, _) if actor.name == players[1].name => Some(TRY_HEAL_ITSELF),
+            Action::Super(_, _) if players[1..].iter().any(|player| player.name == actor.name) => {
+                Some(TRY_SUPER_ITSELF)
+            }
+            Action::Super(_, partners)
+                if (1..partners.len())
+                    .any(|index| partners[..index].contains(&partners[index])) =>
+            {
+                Some(TRY_SUPER_TWICE)
+            }
+            Action::Shield(_, _) if actor.name == players[1].name => Some(TRY_SHIELD_ITSELF),
+            Action::Transfer(_, _, _) if actor.name == players[1].name => Some(TRY_TRANSFER_ITSELF),
+            Action::Revive(_, _) if players[1].power > 0 => Some(NOT_FROZEN),
+            Action::Revive(_, _) if actor.power <= REVIVE_COST => Some(NOT_ENOUGH_POWER),
+            Action::Transfer(_, _, amount) if *amount > actor.power => Some(NOT_ENOUGH_POWER),
+            _ => None,
+        };
+        Some(warning)
+    }
+
+    /// Every action possible with the current players, together with the warning
+    /// it would produce (None if it would be performed). Two kinds are bounded:
+    /// - `transfer` gives 1, half (rounded up) or all of the actor's power
+    /// - `super` has one partner of any team, or up to MAX_PARTNERS distinct teammates,
+    ///   frozen or not
+    ///
+    /// Players are sorted by name, so the order does not depend on the hashmap
+    pub fn classify_actions(&self) -> Vec<(Action, Option<&'static str>)> {
+        let mut names: Vec<&String> = self.players.keys().collect();
+        names.sort();
+
+        let mut actions = Vec::new();
+        for actor in &names {
+            actions.push(Action::FlipVisibility(actor.to_string()));
+            actions.push(Action::Sacrifice(actor.to_string()));
+            actions.push(Action::Split(actor.to_string()));
+            let power = self.players[*actor].power;
+            let mut amounts = vec![1, power.div_ceil(2), power];
+            amounts.dedup();
+            for target in &names {
+                actions.push(Action::Attack(actor.to_string(), target.to_string()));
+                actions.push(Action::Heal(actor.to_string(), target.to_string()));
+                actions.push(Action::Super(actor.to_string(), vec![target.to_string()]));
+                actions.push(Action::Shield(actor.to_string(), target.to_string()));
+                actions.push(Action::Revive(actor.to_string(), target.to_string()));
+                for &amount in amounts.iter().filter(|&&amount| amount > 0) {
+                    let action = Action::Transfer(actor.to_string(), target.to_string(), amount);
+                    actions.push(action);
+                }
+            }
+
+            let mut groups: Vec<Vec<String>> = vec![Vec::new()];
+            let team = self.players[*actor].team_number;
+            let teammates = names
+                .iter()
+                .filter(|name| **name != *actor && self.players[**name].team_number == team);
+            for teammate in teammates {
+                for index in 0..groups.len() {
+                    if groups[index].len() < MAX_PARTNERS {
+                        let mut group = groups[index].clone();
+                        group.push(teammate.to_string());
+                        groups.push(group);
+                    }
+                }
+            }
+            groups.retain(|group| group.len() > 1);
+            groups.sort();
+            for group in groups {
+                actions.push(Action::Super(actor.to_string(), group));
+            }
+        }
+
+        actions
+            .into_iter()
+            .map(|action| {
+                // All players exist, so the action is never invalid
+                let warning = self.warning_of(&action).unwrap();
+                (action, warning)
+            })
+            .collect()
+    }
+
+    /// Actions which would be performed without a warning
+    pub fn legal_actions(&self) -> Vec<Action> {
+        self.classify_actions()
+            .into_iter()
+            .filter(|(_, warning)| warning.is_none())
+            .map(|(action, _)| action)
+            .collect()
+    }
+}
+
+/// `moves <file> [--all]` command. Plays the file and prints the actions without
+/// a warning in the final state, or with `--all` every action and its warning
+pub fn command(args: &[String]) -> Result<(), String> {
+    let (path, all) = match args {
+        [path] => (path, false),
+        [path, flag] if flag == "--all" => (path, true),
+        _ => return Err("Usage: moves <file> [--all]".to_string()),
+    };
+
+    let mut input = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let game = Game::run(&mut input, &Rules::default()).map_err(|e| format!("{}: {}", path, e))?;
+
+    for (action, warning) in game.classify_actions() {
+        match warning {
+            None => println!("{}", action),
+            Some(warning) if all => println!("{}: {}", action, warning),
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crate::{scenario::Scenario, view::Fog, Action, Game, INVALID_INPUT};
+
+/// A game hosted for networked clients, one client per team
+struct Lobby {
+    game: Game,
+    // Client controlling every team
+    claims: Vec<Option<u64>>,
+    // Streams to write to every connected client
+    clients: HashMap<u64, TcpStream>,
+    // What clients do not see of invisible enemies
+    fog: Fog,
+}
+
+impl Lobby {
+    /// Sends the event with the outcome of an action to every client which sees it,
+    /// as its team sees it. Clients which went away are skipped, their own threads
+    /// notice it and leave the lobby
+    fn broadcast(&mut self, magician: &str, event: usize) {
+        let event = &self.game.events[event];
+        for (client, stream) in self.clients.iter_mut() {
+            let team = self.claims.iter().position(|claim| *claim == Some(*client));
+            if let Some(event) = event.seen_by(team, self.fog) {
+                let _ = writeln!(stream, "outcome {} {}", magician, event);
+            }
+        }
+    }
+
+    /// Team claimed by the client
+    fn team_of(&self, client: u64) -> Option<usize> {
+        self.claims.iter().position(|&claim| claim == Some(client))
+    }
+
+    /// Answers a command of the client. Outcomes of actions are broadcast
+    /// to every client instead
+    ///
+    /// Returns the lines to send only to the client
+    fn answer(&mut self, client: u64, command: &str) -> Vec<String> {
+        let (verb, argument) = command.split_once(' ').unwrap_or((command, ""));
+        match verb {
+            "claim" => {
+                let Some(team) = self.game.teams.iter().position(|name| name == argument) else {
+                    return vec![format!("error Unknown magician `{}`", argument)];
+                };
+                if self.team_of(client).is_some() {
+                    return vec!["error A team is already claimed".to_string()];
+                }
+                if self.claims[team].is_some() {
+                    return vec![format!("error {} is already claimed", argument)];
+                }
+                self.claims[team] = Some(client);
+                vec!["ok".to_string()]
+            }
+            "action" => {
+                let Some(team) = self.team_of(client) else {
+                    return vec!["error Claim a team first".to_string()];
+                };
+                let action = match Action::parse(argument) {
+                    Ok(action) => action,
+                    Err(e) => return vec![format!("error {}", e)],
+                };
+                match self.game.players.get(action.actor()) {
+                    None => return vec![format!("error {}", INVALID_INPUT)],
+                    Some(player) if player.team_number != team as u64 => {
+                        return vec!["error The player is not from your team".to_string()]
+                    }
+                    Some(_) => {}
+                }
+                // Effects fired by the action are logged after its outcome
+                let event = self.game.events.len();
+                if let Err(e) = self.game.play(&action) {
+                    return vec![format!("error {}", e)];
+                }
+                self.broadcast(&self.game.teams[team].clone(), event);
+                Vec::new()
+            }
+            "state" => self.game.view_lines(self.team_of(client), self.fog),
+            "verdict" => vec![format!("verdict {}", self.game.verdict())],
+            _ => vec![format!("error Unknown command `{}`", verb)],
+        }
+    }
+}
+
+/// Serves a single client until it sends `quit` or disconnects, then frees its team
+fn session(lobby: &Mutex<Lobby>, client: u64, stream: TcpStream) -> io::Result<()> {
+    lobby
+        .lock()
+        .unwrap()
+        .clients
+        .insert(client, stream.try_clone()?);
+
+    let mut result = Ok(());
+    for command in BufReader::new(stream.try_clone()?).lines() {
+        let command = match command {
+            Ok(command) => command,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+        if command.trim() == "quit" {
+            break;
+        }
+
+        // The answer is written under the lock, so it never interleaves with broadcasts
+        let mut lobby = lobby.lock().unwrap();
+        let answer = lobby.answer(client, command.trim());
+        let stream = lobby.clients.get_mut(&client).unwrap();
+        if let Err(e) = answer
+            .iter()
+            .try_for_each(|line| writeln!(stream, "{}", line))
+        {
+            result = Err(e);
+            break;
+        }
+    }
+
+    let mut lobby = lobby.lock().unwrap();
+    lobby.clients.remove(&client);
+    for claim in lobby.claims.iter_mut() {
+        if *claim == Some(client) {
+            *claim = None;
+        }
+    }
+    result
+}
+
+/// Hosts the game of the roster, every connection is served by its own thread.
+/// Clients send lines:
+/// - `claim <magician>`: takes the team, answered with `ok`
+/// - `action <action>`: performs an action of a player of the claimed team,
+///   every client gets `outcome <magician> <event>` as its team sees the event
+/// - `state`: answered like `view` of the engine protocol for the claimed team,
+///   with the fog of the host
+/// - `verdict`: answered with `verdict <verdict>`
+/// - `quit`: leaves the game and frees the team
+///
+/// Anything wrong is answered with `error <message>` and changes nothing
+pub fn host(listener: TcpListener, roster: &Scenario, fog: Fog) -> io::Result<()> {
+    let lobby = Arc::new(Mutex::new(Lobby {
+        game: roster.roster(),
+        claims: vec![None; roster.teams.len()],
+        clients: HashMap::new(),
+        fog,
+    }));
+
+    for (client, stream) in (0..).zip(listener.incoming()) {
+        let stream = stream?;
+        let lobby = Arc::clone(&lobby);
+        thread::spawn(move || {
+            // A broken connection only affects its own client
+            let _ = session(&lobby, client, stream);
+        });
+    }
+    Ok(())
+}
+
+/// `host <roster file> [--bind ADDRESS:PORT] [--fog off|power|existence]` command.
+/// Hosts a game for one client per team, only on localhost by default
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage = "Usage: host <roster file> [--bind ADDRESS:PORT] [--fog off|power|existence]";
+    let path = args.first().ok_or(usage)?;
+
+    let mut address = "127.0.0.1:7878";
+    let mut fog = Fog::Off;
+    let mut flags = args[1..].iter();
+    while let Some(flag) = flags.next() {
+        let value = flags.next().ok_or(usage)?;
+        match flag.as_str() {
+            "--bind" => address = value,
+            "--fog" => fog = Fog::parse(value).ok_or(usage)?,
+            _ => return Err(usage.to_string()),
+        }
+    }
+
+    let mut input = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let roster = Scenario::read_roster(&mut input).map_err(|e| format!("{}: {}", path, e))?;
+    let listener = TcpListener::bind(address).map_err(|e| format!("{}: {}", address, e))?;
+    println!("Hosting on {}", listener.local_addr().unwrap());
+    host(listener, &roster, fog).map_err(|e| e.to_string())
+}
@@ -0,0 +1,322 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read},
+};
+
+use crate::{rules::Rules, scenario::Scenario, Action, Game, INVALID_INPUT};
+
+/// Rating of a player who has not played yet
+pub const INITIAL_RATING: f64 = 1500.0;
+/// Largest change of a rating after a single game
+const K_FACTOR: f64 = 32.0;
+/// Share of the team outcome in the score of a player, the rest is the individual contribution
+const TEAM_WEIGHT: f64 = 0.75;
+
+/// What a player of the roster did during a game
+#[derive(PartialEq, Clone, Debug)]
+pub struct Contribution {
+    pub name: String,
+    pub team: usize,
+    // Enemies frozen by attacks this player took part in, as the attacker or as the target
+    pub freezes: u64,
+    // The player, or the super player it became part of, is not frozen at the end
+    pub survived: bool,
+}
+
+/// Plays the scenario and finds the contribution of every player of its roster,
+/// in the order of the players section. A super player acts for all players it was made of
+///
+/// Returns the final power of every team and the contributions,
+/// or Err(INVALID_INPUT) if an action refers to a missing player
+pub fn contributions(scenario: &Scenario) -> Result<(Vec<u64>, Vec<Contribution>), &'static str> {
+    let mut game = scenario.roster();
+    // Position of every player of the roster
+    let positions: HashMap<&str, usize> = scenario
+        .players
+        .iter()
+        .enumerate()
+        .map(|(index, player)| (player.name.as_str(), index))
+        .collect();
+    // Players of the roster the current player consists of
+    let members = |game: &Game, name: &str| -> Vec<usize> {
+        let originals = game.originals(name);
+        originals
+            .iter()
+            .map(|name| positions[name.as_str()])
+            .collect()
+    };
+    let mut freezes = vec![0; scenario.players.len()];
+
+    for action in &scenario.actions {
+        let before = game.clone();
+        if game.perform(action)?.is_some() {
+            continue;
+        }
+
+        if let Action::Attack(actor, target) = action {
+            // A shield takes the attack instead of the target
+            let target = &before.shield_for(action).unwrap_or(target.clone());
+            if actor == target {
+                continue;
+            }
+            let (old_actor, old_target) = (&before.players[actor], &before.players[target]);
+            if old_actor.team_number == old_target.team_number {
+                continue;
+            }
+            let frozen =
+                |name: &str, old_power: u64| old_power > 0 && game.players[name].power == 0;
+            if frozen(target, old_target.power) {
+                members(&game, actor)
+                    .iter()
+                    .for_each(|&index| freezes[index] += 1);
+            }
+            if frozen(actor, old_actor.power) {
+                members(&game, target)
+                    .iter()
+                    .for_each(|&index| freezes[index] += 1);
+            }
+        }
+    }
+
+    let mut survived = vec![false; scenario.players.len()];
+    for (name, player) in &game.players {
+        if player.power > 0 {
+            members(&game, name)
+                .iter()
+                .for_each(|&index| survived[index] = true);
+        }
+    }
+
+    let contributions = scenario
+        .players
+        .iter()
+        .enumerate()
+        .map(|(index, player)| Contribution {
+            name: player.name.clone(),
+            team: player.team_number as usize,
+            freezes: freezes[index],
+            survived: survived[index],
+        })
+        .collect();
+    Ok((game.team_powers(), contributions))
+}
+
+/// Rating of a player after a game
+#[derive(PartialEq, Clone, Debug)]
+pub struct Change {
+    // Index of the game in the store
+    pub game: usize,
+    pub rating: f64,
+    pub freezes: u64,
+    pub survived: bool,
+}
+
+/// Ratings of all players and the games they were computed from
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Ratings {
+    // Labels of the recorded games, usually the paths of their files
+    pub games: Vec<String>,
+    // History of every player, sorted by name
+    pub players: BTreeMap<String, Vec<Change>>,
+}
+
+impl Ratings {
+    /// Current rating of the player
+    pub fn rating(&self, name: &str) -> f64 {
+        self.players
+            .get(name)
+            .and_then(|history| history.last())
+            .map_or(INITIAL_RATING, |change| change.rating)
+    }
+
+    /// Plays the scenario and updates ratings of its players. Every pair of teams
+    /// is an Elo game decided by the final team powers, a team is rated by the mean
+    /// rating of its players. The score of a player mixes the team outcome with
+    /// the individual contribution: survival and freezes caused
+    ///
+    /// Returns Err(INVALID_INPUT) if an action refers to a missing player
+    pub fn record(&mut self, label: &str, scenario: &Scenario) -> Result<(), &'static str> {
+        let (powers, contributions) = contributions(scenario)?;
+
+        let mut team_ratings = vec![Vec::new(); scenario.teams.len()];
+        for contribution in &contributions {
+            team_ratings[contribution.team].push(self.rating(&contribution.name));
+        }
+        let mean = |ratings: &Vec<f64>| ratings.iter().sum::<f64>() / ratings.len() as f64;
+        // Teams without players take no part in the game
+        let teams: Vec<(usize, f64)> = team_ratings
+            .iter()
+            .enumerate()
+            .filter(|(_, ratings)| !ratings.is_empty())
+            .map(|(team, ratings)| (team, mean(ratings)))
+            .collect();
+
+        // Actual and expected score of every team against all other teams
+        let mut outcomes = vec![(0.5, 0.5); scenario.teams.len()];
+        for &(team, rating) in &teams {
+            let opponents = teams.iter().filter(|(other, _)| *other != team);
+            let (mut actual, mut expected, mut total) = (0.0, 0.0, 0.0);
+            for &(other, other_rating) in opponents {
+                actual += match powers[team].cmp(&powers[other]) {
+                    Ordering::Greater => 1.0,
+                    Ordering::Equal => 0.5,
+                    Ordering::Less => 0.0,
+                };
+                expected += 1.0 / (1.0 + 10f64.powf((other_rating - rating) / 400.0));
+                total += 1.0;
+            }
+            if total > 0.0 {
+                outcomes[team] = (actual / total, expected / total);
+            }
+        }
+
+        let game = self.games.len();
+        for contribution in contributions {
+            let (actual, expected) = outcomes[contribution.team];
+            let freezes = contribution.freezes as f64;
+            let individual = (contribution.survived as u8 as f64 + freezes / (freezes + 1.0)) / 2.0;
+            let score = TEAM_WEIGHT * actual + (1.0 - TEAM_WEIGHT) * individual;
+            let rating = self.rating(&contribution.name) + K_FACTOR * (score - expected);
+
+            self.players
+                .entry(contribution.name)
+                .or_default()
+                .push(Change {
+                    game,
+                    rating,
+                    freezes: contribution.freezes,
+                    survived: contribution.survived,
+                });
+        }
+        self.games.push(label.to_string());
+        Ok(())
+    }
+
+    /// Names of all players with their ratings, from the highest rating to the lowest
+    pub fn leaderboard(&self) -> Vec<(&str, f64)> {
+        let mut leaderboard: Vec<(&str, f64)> = self
+            .players
+            .keys()
+            .map(|name| (name.as_str(), self.rating(name)))
+            .collect();
+        // Names are already sorted, so the stable sort keeps equal ratings in name order
+        leaderboard.sort_by(|a, b| b.1.total_cmp(&a.1));
+        leaderboard
+    }
+
+    /// Reads the store written by `Display`: every game starts with a `game <label>` line,
+    /// followed by a `<name> <rating> <freezes> <survived>` line per player
+    ///
+    /// Returns Err(INVALID_INPUT) if the store is malformed
+    pub fn read(data_source: &mut impl Read) -> Result<Self, &'static str> {
+        let mut ratings = Ratings::default();
+        for line in BufReader::new(data_source).lines() {
+            let line = line.map_err(|_| INVALID_INPUT)?;
+            if let Some(label) = line.strip_prefix("game ") {
+                ratings.games.push(label.to_string());
+                continue;
+            }
+
+            let game = ratings.games.len().checked_sub(1).ok_or(INVALID_INPUT)?;
+            let [name, rating, freezes, survived] = line.split(' ').collect::<Vec<_>>()[..] else {
+                return Err(INVALID_INPUT);
+            };
+            let change = Change {
+                game,
+                rating: rating.parse().map_err(|_| INVALID_INPUT)?,
+                freezes: freezes.parse().map_err(|_| INVALID_INPUT)?,
+                survived: match survived {
+                    "True" => true,
+                    "False" => false,
+                    _ => return Err(INVALID_INPUT),
+                },
+            };
+            ratings
+                .players
+                .entry(name.to_string())
+                .or_default()
+                .push(change);
+        }
+        Ok(ratings)
+    }
+
+    /// Writes the store to a temporary file and moves it over the old one,
+    /// so an interrupted write never leaves a half written store
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let temporary = format!("{}.tmp", path);
+        fs::write(&temporary, self.to_string())?;
+        File::open(&temporary)?.sync_all()?;
+        fs::rename(&temporary, path)
+    }
+}
+
+impl fmt::Display for Ratings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (game, label) in self.games.iter().enumerate() {
+            writeln!(f, "game {}", label)?;
+            for (name, history) in &self.players {
+                for change in history.iter().filter(|change| change.game == game) {
+                    let survived = if change.survived { "True" } else { "False" };
+                    writeln!(
+                        f,
+                        "{} {:.2} {} {}",
+                        name, change.rating, change.freezes, survived
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `ratings <store> [<match file>...]` command. Records the matches in the store,
+/// then prints the leaderboard and the history of every player
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage = "Usage: ratings <store> [<match file>...]";
+    let (store, matches) = args.split_first().ok_or(usage)?;
+
+    let mut ratings = match File::open(store) {
+        Ok(mut input) => Ratings::read(&mut input).map_err(|e| format!("{}: {}", store, e))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ratings::default(),
+        Err(e) => return Err(format!("{}: {}", store, e)),
+    };
+    if !matches.is_empty() {
+        for path in matches {
+            let mut input = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+            Scenario::read(&mut input, &Rules::default())
+                .and_then(|scenario| ratings.record(path, &scenario))
+                .map_err(|e| format!("{}: {}", path, e))?;
+        }
+        ratings
+            .save(store)
+            .map_err(|e| format!("{}: {}", store, e))?;
+    }
+
+    for (place, (name, rating)) in ratings.leaderboard().iter().enumerate() {
+        println!("{}. {} {:.0}", place + 1, name, rating);
+    }
+    for (name, history) in &ratings.players {
+        println!();
+        println!("{}:", name);
+        let mut previous = INITIAL_RATING;
+        for change in history {
+            println!(
+                "  {}: {:.0} ({:+.0}), {} freezes, {}",
+                ratings.games[change.game],
+                change.rating,
+                change.rating - previous,
+                change.freezes,
+                if change.survived {
+                    "survived"
+                } else {
+                    "frozen"
+                }
+            );
+            previous = change.rating;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,277 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+use crate::{
+    event::{Event, Outcome},
+    tactics::{REVIVE_COST, REVIVE_POWER},
+    Action, Game, Player, INVALID_INPUT,
+};
+
+/// Line ending a round of simultaneous actions
+pub const END_ROUND: &str = "end_round";
+
+impl Game {
+    /// Plays a round of simultaneous actions. Every action is checked and computed
+    /// against the state at the start of the round, then the results are combined:
+    /// - warnings are the same as for the starting state, in the order of the round
+    /// - changes of power add up: an attack gives the winner the difference of the
+    ///   starting powers, a heal moves half of the healer's starting power
+    /// - a player frozen by any action stays frozen, whatever it gains in the round
+    /// - attacks see the starting visibility, flips of visibility apply at the end
+    /// - super and split actions are done last with the resulting powers, in the order
+    ///   of the round. They are cancelled for players frozen in the round and for players
+    ///   merged or split earlier in the round. Cancelled actions take no energy and no turn
+    ///
+    /// Every conflict between actions is written to the event log after the action
+    ///
+    /// Returns Err(INVALID_INPUT) if an action refers to a player that does not exist
+    /// at the start of the round, nothing is changed then
+    pub fn play_round(&mut self, actions: &[Action]) -> Result<(), &'static str> {
+        if actions
+            .iter()
+            .flat_map(Action::players)
+            .any(|name| !self.players.contains_key(name))
+        {
+            return Err(INVALID_INPUT);
+        }
+        // Warnings see the turns and the energy taken by the earlier actions of the round.
+        // They are found on a copy, the round itself ends only once, after it is resolved
+        let mut warnings = Vec::new();
+        let mut scratch = self.clone();
+        for action in actions {
+            let warning = scratch.warning_of(action).ok_or(INVALID_INPUT)?;
+            if warning.is_none() {
+                scratch.pay_energy(action);
+                let team = scratch.players[action.actor()].team_number;
+                scratch.count_turn(team, action.actor());
+            }
+            warnings.push(warning);
+        }
+        // Super and split actions pay once they are not cancelled
+        let mut performed: Vec<bool> = warnings.iter().map(Option::is_none).collect();
+        for (action, &performed) in actions.iter().zip(&performed) {
+            if performed && !matches!(action, Action::Super(_, _) | Action::Split(_)) {
+                self.pay_energy(action);
+            }
+        }
+        let start = self.players.clone();
+        // Changed players and shields taking attacks, as they are at the start of the round
+        let affected: Vec<Vec<String>> =
+            actions.iter().map(|action| self.affected(action)).collect();
+        let involved: Vec<Vec<Player>> =
+            actions.iter().map(|action| self.involved(action)).collect();
+        let shields: Vec<Option<String>> = actions
+            .iter()
+            .map(|action| self.shield_for(action))
+            .collect();
+
+        let mut delta: HashMap<&str, i64> = HashMap::new();
+        let mut frozen: HashSet<&str> = HashSet::new();
+        let mut flipped: HashSet<&str> = HashSet::new();
+        // Actions giving power to a player, by their index in the round
+        let mut gains: Vec<(usize, &str)> = Vec::new();
+        // Attacks on every player, by their index in the round
+        let mut attacks: HashMap<&str, Vec<usize>> = HashMap::new();
+        // Shields are used up and set after the round
+        let mut used_shields: Vec<&str> = Vec::new();
+        let mut new_shields: Vec<(&str, &str)> = Vec::new();
+
+        for (index, (action, warning)) in actions.iter().zip(&warnings).enumerate() {
+            if warning.is_some() {
+                continue;
+            }
+            match action {
+                Action::Attack(actor, target) => {
+                    let target = match &shields[index] {
+                        Some(shield) => {
+                            used_shields.push(target);
+                            shield
+                        }
+                        None => target,
+                    };
+                    attacks.entry(target).or_default().push(index);
+                    if !start[target].is_visible {
+                        frozen.insert(actor);
+                        continue;
+                    }
+                    let (power, target_power) = (start[actor].power, start[target].power);
+                    let (winner, loser) = match power.cmp(&target_power) {
+                        Ordering::Greater => (actor.as_str(), target.as_str()),
+                        Ordering::Less => (target.as_str(), actor.as_str()),
+                        Ordering::Equal => {
+                            frozen.extend([actor.as_str(), target.as_str()]);
+                            continue;
+                        }
+                    };
+                    frozen.insert(loser);
+                    *delta.entry(winner).or_default() += power.abs_diff(target_power) as i64;
+                    gains.push((index, winner));
+                }
+                Action::Heal(actor, target) => {
+                    let power = start[actor].power;
+                    let heal_points = power.div_ceil(2);
+                    *delta.entry(actor).or_default() -= (power - heal_points) as i64;
+                    *delta.entry(target).or_default() += heal_points as i64;
+                    gains.push((index, target));
+                }
+                Action::FlipVisibility(actor) => {
+                    // Two flips in a round cancel each other
+                    if !flipped.insert(actor) {
+                        flipped.remove(actor.as_str());
+                    }
+                }
+                Action::Shield(actor, target) => new_shields.push((actor, target)),
+                Action::Revive(actor, target) => {
+                    *delta.entry(actor).or_default() -= REVIVE_COST as i64;
+                    *delta.entry(target).or_default() += REVIVE_POWER as i64;
+                    gains.push((index, target));
+                }
+                Action::Transfer(actor, target, amount) => {
+                    *delta.entry(actor).or_default() -= *amount as i64;
+                    *delta.entry(target).or_default() += *amount as i64;
+                    gains.push((index, target));
+                }
+                Action::Sacrifice(actor) => {
+                    frozen.insert(actor);
+                    let teammates = &affected[index][1..];
+                    for teammate in teammates {
+                        let share = start[actor].power / teammates.len() as u64;
+                        *delta.entry(teammate).or_default() += share as i64;
+                        gains.push((index, teammate));
+                    }
+                }
+                Action::Super(_, _) | Action::Split(_) => {}
+            }
+        }
+        for target in used_shields {
+            self.shields.remove(target);
+        }
+        for (actor, target) in new_shields {
+            self.shields.insert(actor.to_string(), target.to_string());
+        }
+
+        for (name, delta) in &delta {
+            let player = self.players.get_mut(*name).unwrap();
+            player.power = (player.power as i64 + delta).clamp(0, 1000) as u64;
+        }
+        for name in &frozen {
+            self.players.get_mut(*name).unwrap().power = 0;
+        }
+        for name in &flipped {
+            let player = self.players.get_mut(*name).unwrap();
+            player.is_visible = !player.is_visible;
+        }
+
+        let mut log: Vec<Vec<Outcome>> = vec![Vec::new(); actions.len()];
+        for (index, (action, warning)) in actions.iter().zip(&warnings).enumerate() {
+            if let Some(warning) = warning {
+                self.warnings.push(warning);
+                log[index].push(Outcome::Warning(warning));
+            } else if !matches!(action, Action::Super(_, _) | Action::Split(_)) {
+                let players = affected[index]
+                    .iter()
+                    .map(|name| self.players[name].clone());
+                log[index].push(Outcome::Changed(players.collect()));
+            }
+        }
+
+        for (index, name) in gains {
+            if frozen.contains(name) {
+                let message = format!("{} is frozen in the round, its gain is lost", name);
+                log[index].push(Outcome::Conflict(message));
+            }
+        }
+        for (target, indices) in &attacks {
+            for &index in &indices[1..] {
+                let message = format!("{} is attacked by several players in the round", target);
+                log[index].push(Outcome::Conflict(message));
+            }
+            if flipped.contains(target) {
+                for &index in indices {
+                    let message = format!(
+                        "{} flips its visibility in the round, the attack sees its visibility \
+                         at the start of the round",
+                        target
+                    );
+                    log[index].push(Outcome::Conflict(message));
+                }
+            }
+        }
+
+        let mut merged: HashSet<&str> = HashSet::new();
+        for (index, (action, warning)) in actions.iter().zip(&warnings).enumerate() {
+            let verb = match (action, warning) {
+                (Action::Super(_, _), None) => "super",
+                (Action::Split(_), None) => "split",
+                _ => continue,
+            };
+            let names = action.players();
+
+            if let Some(name) = names.iter().find(|name| merged.contains(**name)) {
+                let message = format!(
+                    "{} is already merged or split in the round, the {} is cancelled",
+                    name, verb
+                );
+                log[index].push(Outcome::Conflict(message));
+                performed[index] = false;
+                continue;
+            }
+            // Partners frozen before the round may take part, as in sequential play
+            if let Some(name) = names
+                .iter()
+                .find(|name| start[**name].power > 0 && self.players[**name].power == 0)
+            {
+                let message = format!("{} is frozen in the round, the {} is cancelled", name, verb);
+                log[index].push(Outcome::Conflict(message));
+                performed[index] = false;
+                continue;
+            }
+            for name in names.iter().filter(|name| attacks.contains_key(**name)) {
+                let message = format!(
+                    "{} is attacked in the round, the {} uses its power after the round",
+                    name, verb
+                );
+                log[index].push(Outcome::Conflict(message));
+            }
+            merged.extend(names.iter().copied());
+            self.pay_energy(action);
+
+            let changed = match action {
+                Action::Split(actor) => {
+                    self.split(actor);
+                    let parts = affected[index].iter();
+                    parts.map(|name| self.players[name].clone()).collect()
+                }
+                Action::Super(_, _) => {
+                    let parts = names.iter().map(|name| self.players[*name].clone());
+                    vec![self.fuse(parts.collect())]
+                }
+                _ => continue,
+            };
+            log[index].insert(0, Outcome::Changed(changed));
+        }
+
+        for ((action, outcomes), involved) in actions.iter().zip(log).zip(involved) {
+            for outcome in outcomes {
+                self.events.push(Event {
+                    action: Some(action.clone()),
+                    outcome,
+                    involved: involved.clone(),
+                });
+            }
+        }
+        // Turns are taken in the order of the round
+        for (action, &performed) in actions.iter().zip(&performed) {
+            if performed {
+                self.count_turn(start[action.actor()].team_number, action.actor());
+            }
+        }
+        let mut names: Vec<String> = self.players.keys().cloned().collect();
+        names.sort();
+        self.tick_actions(actions.len() as u64, &names);
+        self.end_round();
+        Ok(())
+    }
+}
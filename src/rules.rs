@@ -0,0 +1,103 @@
+use crate::{
+    effects::{Duration, Effects},
+    energy::Energy,
+    lineage::SplitRule,
+    turns::TurnPolicy,
+};
+
+/// Optional deviations from the task. The default value follows the task exactly
+#[derive(Clone, Default, Debug)]
+pub struct Rules {
+    // Accept CRLF line endings, whitespace around and between words and blank lines
+    pub tolerant_input: bool,
+    // Group actions into rounds ended by `end_round` lines and resolve every round
+    // against the state at its start, see `Game::play_round`
+    pub simultaneous_rounds: bool,
+    // Which actions teams may take, others are ignored with OUT_OF_TURN warnings.
+    // `end_round` lines start a new round of the policy
+    pub turn_policy: TurnPolicy,
+    // Costs of actions in energy, actions are free if None.
+    // Players regenerate energy at the end of every round
+    pub energy: Option<Energy>,
+    // Durations of invisibility and freezing, both last forever by default
+    pub effects: Effects,
+    // How split super players share their power
+    pub split_rule: SplitRule,
+}
+
+impl Rules {
+    /// Command line flags understood by `from_args`
+    pub const USAGE: &'static str = "[--tolerant] [--rounds] [--turns=rotation|player|budget:N] \
+                                     [--energy[=name:cost,...]] \
+                                     [--invisibility=N:actions|N:rounds] [--thaw=K:POWER] \
+                                     [--split=proportional|equal]";
+
+    /// Splits command line arguments into rules and the remaining arguments
+    ///
+    /// Returns Err(message) if a flag is unknown
+    pub fn from_args(args: &[String]) -> Result<(Self, Vec<String>), String> {
+        let mut rules = Rules::default();
+        let mut rest = Vec::new();
+
+        for arg in args {
+            match arg.as_str() {
+                "--tolerant" => rules.tolerant_input = true,
+                "--rounds" => rules.simultaneous_rounds = true,
+                "--energy" => rules.energy = Some(Energy::default()),
+                flag if flag.starts_with("--energy=") => {
+                    rules.energy = Some(
+                        Energy::parse(&flag["--energy=".len()..])
+                            .ok_or_else(|| format!("Unknown energy costs in `{}`", flag))?,
+                    )
+                }
+                flag if flag.starts_with("--invisibility=") => {
+                    rules.effects.invisibility = Some(
+                        Duration::parse(&flag["--invisibility=".len()..])
+                            .ok_or_else(|| format!("Unknown duration in `{}`", flag))?,
+                    )
+                }
+                flag if flag.starts_with("--thaw=") => {
+                    let thaw =
+                        flag["--thaw=".len()..]
+                            .split_once(':')
+                            .and_then(|(rounds, power)| {
+                                let rounds = rounds.parse().ok().filter(|&rounds| rounds > 0)?;
+                                let power = power
+                                    .parse()
+                                    .ok()
+                                    .filter(|power| (1..=1000).contains(power))?;
+                                Some((rounds, power))
+                            });
+                    rules.effects.thaw =
+                        Some(thaw.ok_or_else(|| format!("Unknown thaw in `{}`", flag))?);
+                }
+                flag if flag.starts_with("--split=") => {
+                    rules.split_rule = SplitRule::parse(&flag["--split=".len()..])
+                        .ok_or_else(|| format!("Unknown split rule in `{}`", flag))?
+                }
+                flag if flag.starts_with("--turns=") => {
+                    rules.turn_policy = TurnPolicy::parse(&flag["--turns=".len()..])
+                        .ok_or_else(|| format!("Unknown turn policy in `{}`", flag))?
+                }
+                flag if flag.starts_with("--") => {
+                    return Err(format!(
+                        "Unknown rule `{}`, expected {}",
+                        flag,
+                        Rules::USAGE
+                    ))
+                }
+                _ => rest.push(arg.clone()),
+            }
+        }
+
+        Ok((rules, rest))
+    }
+
+    /// Checks whether `end_round` lines are a part of the actions section
+    pub fn uses_rounds(&self) -> bool {
+        self.simultaneous_rounds
+            || self.turn_policy != TurnPolicy::Free
+            || self.energy.is_some()
+            || self.effects != Effects::default()
+    }
+}
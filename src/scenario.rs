@@ -0,0 +1,94 @@
+use std::{
+    fmt,
+    io::{self, Read},
+};
+
+use crate::{input_lines, rules::Rules, Action, Game, Player, INVALID_INPUT};
+
+/// Structure representing the whole input file: the roster and the actions,
+/// in the order they are written
+#[derive(PartialEq, Clone, Debug)]
+pub struct Scenario {
+    // Magicians chosen by the teams
+    pub teams: Vec<String>,
+    // Players in the order of the players section
+    pub players: Vec<Player>,
+    // Actions in the order of the actions section
+    pub actions: Vec<Action>,
+}
+
+impl Scenario {
+    /// Reads only the teams and the players sections, the rest of the input is ignored
+    ///
+    /// Returns Err(INVALID_INPUT) if the roster is malformed
+    pub fn read_roster(data_source: &mut impl Read) -> Result<Self, &'static str> {
+        let (teams, players) = Game::read_roster(&mut input_lines(data_source, &Rules::default()))?;
+        Ok(Self {
+            teams,
+            players,
+            actions: Vec::new(),
+        })
+    }
+
+    /// Reads and parses the whole input. Players are not looked up in actions,
+    /// so a scenario can still turn out invalid while being played
+    ///
+    /// Returns Err(INVALID_INPUT) if any line of the input is malformed
+    pub fn read(data_source: &mut impl Read, rules: &Rules) -> Result<Self, &'static str> {
+        let mut buffer = input_lines(data_source, rules);
+
+        let (teams, players) = Game::read_roster(&mut buffer)?;
+        let actions = buffer
+            .collect::<io::Result<Vec<String>>>()
+            .map_err(|_| INVALID_INPUT)?
+            .iter()
+            .map(|line| Action::parse(line))
+            .collect::<Result<Vec<Action>, &'static str>>()?;
+
+        Ok(Self {
+            teams,
+            players,
+            actions,
+        })
+    }
+
+    /// Game before any action of the scenario was performed
+    pub fn roster(&self) -> Game {
+        Game::new(self.teams.clone(), self.players.clone())
+    }
+
+    /// Performs all actions of the scenario, warnings and events are collected like in `Game::run`
+    ///
+    /// Returns Err(INVALID_INPUT) if an action refers to a missing player
+    pub fn play(&self) -> Result<Game, &'static str> {
+        let mut game = self.roster();
+        for action in &self.actions {
+            game.play(action)?;
+        }
+        Ok(game)
+    }
+}
+
+impl fmt::Display for Scenario {
+    /// Writes the scenario in canonical form: one value per line,
+    /// single spaces between words and a line feed after every line
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.teams.len())?;
+        for team in &self.teams {
+            writeln!(f, "{}", team)?;
+        }
+
+        writeln!(f, "{}", self.players.len())?;
+        for player in &self.players {
+            writeln!(f, "{}", player.name)?;
+            writeln!(f, "{}", player.team_number)?;
+            writeln!(f, "{}", player.power)?;
+            writeln!(f, "{}", if player.is_visible { "True" } else { "False" })?;
+        }
+
+        for action in &self.actions {
+            writeln!(f, "{}", action)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    thread,
+};
+
+use crate::{json::Json, rules::Rules, Action, Game};
+
+/// Largest accepted request body, scenarios are far smaller
+const MAX_BODY: usize = 1 << 20;
+
+/// Games of the server keyed by their IDs. Every game has its own lock,
+/// so requests to different games never wait for each other, and requests
+/// reading the same game only wait for actions being performed
+#[derive(Default)]
+pub struct Server {
+    games: RwLock<HashMap<u64, Arc<RwLock<Game>>>>,
+    // The last given ID
+    last_id: AtomicU64,
+}
+
+impl Server {
+    /// Answers a single request. Routes:
+    /// - `POST /games` with `{"scenario": text}` creates a game from the input file text
+    /// - `GET /games/<id>` returns the state
+    /// - `POST /games/<id>/actions` with `{"action": line}` performs the action
+    /// - `GET /games/<id>/standings` returns team powers from the strongest team
+    /// - `GET /games/<id>/events` returns the event log
+    ///
+    /// Returns the HTTP status and the JSON body
+    pub fn handle(&self, method: &str, path: &str, body: &str) -> (u16, Json) {
+        let route: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+        let (id, rest) = match route.as_slice() {
+            ["games"] if method == "POST" => return self.create(body),
+            ["games", id, rest @ ..] => match id.parse::<u64>() {
+                Ok(id) => (id, rest),
+                Err(_) => return error(404, "Unknown game"),
+            },
+            _ => return error(404, "Not found"),
+        };
+
+        let Some(lock) = self.games.read().unwrap().get(&id).cloned() else {
+            return error(404, "Unknown game");
+        };
+        match (method, rest) {
+            ("GET", []) => (200, state(id, &lock.read().unwrap())),
+            ("GET", ["standings"]) => (200, standings(&lock.read().unwrap())),
+            ("GET", ["events"]) => {
+                let game = lock.read().unwrap();
+                let events: Vec<String> =
+                    game.events.iter().map(|event| event.to_string()).collect();
+                (200, Json::object([("events", strings(&events))]))
+            }
+            ("POST", ["actions"]) => {
+                let mut game = lock.write().unwrap();
+                let Some(line) = field(body, "action") else {
+                    return error(400, "Expected {\"action\": line}");
+                };
+                // Effects fired by the action are logged after its outcome
+                let (warnings, events) = (game.warnings.len(), game.events.len());
+                match Action::parse(&line).and_then(|action| {
+                    game.play(&action)?;
+                    Ok(action)
+                }) {
+                    // The game stays as it was, invalid actions are not a part of it
+                    Err(e) => error(400, e),
+                    Ok(action) => {
+                        let warning = game.warnings[warnings..]
+                            .first()
+                            .map_or(Json::Null, |&warning| warning.into());
+                        let event = game.events[events].to_string();
+                        (
+                            200,
+                            Json::object([
+                                ("action", action.to_string().as_str().into()),
+                                ("warning", warning),
+                                ("event", event.as_str().into()),
+                            ]),
+                        )
+                    }
+                }
+            }
+            _ => error(404, "Not found"),
+        }
+    }
+
+    /// Plays the scenario (a roster, possibly with actions) and keeps the game under a new ID
+    fn create(&self, body: &str) -> (u16, Json) {
+        let Some(scenario) = field(body, "scenario") else {
+            return error(400, "Expected {\"scenario\": text}");
+        };
+        match Game::run(&mut scenario.as_bytes(), &Rules::default()) {
+            Err(e) => error(400, e),
+            Ok(game) => {
+                let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+                let state = state(id, &game);
+                self.games
+                    .write()
+                    .unwrap()
+                    .insert(id, Arc::new(RwLock::new(game)));
+                (201, state)
+            }
+        }
+    }
+}
+
+/// String field of the JSON object in the body
+fn field(body: &str, key: &str) -> Option<String> {
+    Json::parse(body)?.get(key)?.as_str().map(str::to_string)
+}
+
+fn error(status: u16, message: &str) -> (u16, Json) {
+    (status, Json::object([("error", message.into())]))
+}
+
+fn strings(values: &[impl AsRef<str>]) -> Json {
+    Json::Array(values.iter().map(|value| value.as_ref().into()).collect())
+}
+
+/// Teams, players sorted by name, warnings and the current verdict
+fn state(id: u64, game: &Game) -> Json {
+    let mut names: Vec<&String> = game.players.keys().collect();
+    names.sort();
+    let players = names
+        .into_iter()
+        .map(|name| {
+            let player = &game.players[name];
+            Json::object([
+                ("name", name.as_str().into()),
+                ("team", player.team_number.into()),
+                ("power", player.power.into()),
+                ("visible", Json::Bool(player.is_visible)),
+                ("energy", player.energy.map_or(Json::Null, Json::from)),
+            ])
+        })
+        .collect();
+    Json::object([
+        ("id", id.into()),
+        ("teams", strings(&game.teams)),
+        ("players", Json::Array(players)),
+        ("warnings", strings(&game.warnings)),
+        ("verdict", game.verdict().as_str().into()),
+    ])
+}
+
+/// Teams from the strongest to the weakest, equal teams in the order of their numbers
+fn standings(game: &Game) -> Json {
+    let powers = game.team_powers();
+    let mut teams: Vec<usize> = (0..game.teams.len()).collect();
+    teams.sort_by_key(|&team| std::cmp::Reverse(powers[team]));
+    let standings = teams
+        .into_iter()
+        .map(|team| {
+            Json::object([
+                ("team", (team as u64).into()),
+                ("magician", game.teams[team].as_str().into()),
+                ("power", powers[team].into()),
+            ])
+        })
+        .collect();
+    Json::object([
+        ("standings", Json::Array(standings)),
+        ("verdict", game.verdict().as_str().into()),
+    ])
+}
+
+/// Reads a request: the method, the path and the body of `Content-Length` bytes
+fn read_request(stream: &mut impl BufRead) -> io::Result<(String, String, String)> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed request");
+
+    let mut line = String::new();
+    stream.read_line(&mut line)?;
+    let mut words = line.split_whitespace();
+    let method = words.next().ok_or_else(invalid)?.to_string();
+    let path = words.next().ok_or_else(invalid)?.to_string();
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if stream.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().map_err(|_| invalid())?;
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(invalid());
+    }
+
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body)?;
+    Ok((
+        method,
+        path,
+        String::from_utf8(body).map_err(|_| invalid())?,
+    ))
+}
+
+/// Answers a single request on the connection and closes it
+fn respond(server: &Server, mut stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let (status, body) = match read_request(&mut reader) {
+        Ok((method, path, body)) => server.handle(&method, &path, &body),
+        Err(_) => error(400, "Malformed request"),
+    };
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        _ => "Not Found",
+    };
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Accepts connections forever, every connection is answered by its own thread
+pub fn serve(listener: TcpListener, server: Arc<Server>) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let server = Arc::clone(&server);
+        thread::spawn(move || {
+            // A client which went away only affects its own connection
+            let _ = respond(&server, stream);
+        });
+    }
+    Ok(())
+}
+
+/// `serve [--port P]` command. Serves the game engine over HTTP on localhost
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage = "Usage: serve [--port P]";
+    let port: u16 = match args {
+        [] => 8080,
+        [flag, port] if flag == "--port" => port.parse().map_err(|_| usage)?,
+        _ => return Err(usage.to_string()),
+    };
+
+    let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|e| e.to_string())?;
+    println!("Listening on http://{}", listener.local_addr().unwrap());
+    serve(listener, Arc::new(Server::default())).map_err(|e| e.to_string())
+}
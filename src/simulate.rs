@@ -0,0 +1,156 @@
+use std::{fs::File, thread};
+
+use crate::{random::Random, scenario::Scenario, Game};
+
+/// Settings of a Monte Carlo simulation
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    pub seed: u64,
+    // Amount of simulated games
+    pub games: u64,
+    // Maximal amount of actions in a game, a game also ends without legal actions
+    pub actions: usize,
+    // Amount of worker threads, the result does not depend on it
+    pub threads: usize,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            games: 10000,
+            actions: 10,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+}
+
+/// Results of a simulation: how many games every team won and how many were tied
+#[derive(PartialEq, Clone, Debug)]
+pub struct Estimate {
+    pub games: u64,
+    // Wins by team index
+    pub wins: Vec<u64>,
+    pub ties: u64,
+}
+
+/// Wilson score interval with 95% confidence for `successes` out of `total`
+pub fn confidence_interval(successes: u64, total: u64) -> (f64, f64) {
+    if total == 0 {
+        return (0.0, 1.0);
+    }
+    let z = 1.96;
+    let n = total as f64;
+    let p = successes as f64 / n;
+    let center = (p + z * z / (2.0 * n)) / (1.0 + z * z / n);
+    let margin = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / (1.0 + z * z / n);
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+/// Plays a single game of random legal actions and returns the winner index
+fn play_random(roster: &Game, config: &SimulationConfig, random: &mut Random) -> Option<usize> {
+    let mut game = roster.clone();
+    for _ in 0..config.actions {
+        let actions = game.legal_actions();
+        if actions.is_empty() {
+            break;
+        }
+        game.perform(random.pick(&actions)).unwrap();
+    }
+    game.get_winner_index()
+}
+
+/// Plays the configured amount of random games from the roster on all worker threads.
+/// Every game has its own random stream, so the result only depends on the seed
+pub fn simulate(roster: &Game, config: &SimulationConfig) -> Estimate {
+    let threads = config.threads.max(1) as u64;
+
+    let partial: Vec<Estimate> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                // Workers share the roster, every game is played on its own copy
+                scope.spawn(move || {
+                    let mut estimate = Estimate {
+                        games: 0,
+                        wins: vec![0; roster.teams.len()],
+                        ties: 0,
+                    };
+                    for index in (worker..config.games).step_by(threads as usize) {
+                        let mut random = Random::stream(config.seed, index);
+                        match play_random(roster, config, &mut random) {
+                            Some(winner) => estimate.wins[winner] += 1,
+                            None => estimate.ties += 1,
+                        }
+                        estimate.games += 1;
+                    }
+                    estimate
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    let mut total = Estimate {
+        games: 0,
+        wins: vec![0; roster.teams.len()],
+        ties: 0,
+    };
+    for estimate in partial {
+        total.games += estimate.games;
+        total.ties += estimate.ties;
+        for (team, wins) in estimate.wins.iter().enumerate() {
+            total.wins[team] += wins;
+        }
+    }
+    total
+}
+
+/// `simulate <file> [--games N] [--actions K] [--seed S] [--threads T]` command.
+/// Only the roster of the file is used, its actions are ignored
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage = "Usage: simulate <file> [--games N] [--actions K] [--seed S] [--threads T]";
+    let path = args.first().ok_or(usage)?;
+
+    let mut config = SimulationConfig::default();
+    let mut flags = args[1..].iter();
+    while let Some(flag) = flags.next() {
+        let value = flags.next().ok_or(usage)?;
+        let invalid = || format!("Invalid value `{}` of `{}`", value, flag);
+        match flag.as_str() {
+            "--games" => config.games = value.parse().map_err(|_| invalid())?,
+            "--actions" => config.actions = value.parse().map_err(|_| invalid())?,
+            "--seed" => config.seed = value.parse().map_err(|_| invalid())?,
+            "--threads" => config.threads = value.parse().map_err(|_| invalid())?,
+            _ => return Err(usage.to_string()),
+        }
+    }
+
+    let mut input = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let roster = Scenario::read_roster(&mut input)
+        .map_err(|e| format!("{}: {}", path, e))?
+        .roster();
+
+    let estimate = simulate(&roster, &config);
+    println!(
+        "Games: {}, actions per game: {}, seed: {}",
+        estimate.games, config.actions, config.seed
+    );
+    let report = |name: &str, count: u64| {
+        let (low, high) = confidence_interval(count, estimate.games);
+        println!(
+            "{}: {:.2}% (95% CI {:.2}%-{:.2}%)",
+            name,
+            100.0 * count as f64 / estimate.games.max(1) as f64,
+            100.0 * low,
+            100.0 * high
+        );
+    };
+    for (team, magician) in roster.teams.iter().enumerate() {
+        report(magician, estimate.wins[team]);
+    }
+    report("Tie", estimate.ties);
+    Ok(())
+}
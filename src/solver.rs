@@ -0,0 +1,163 @@
+use std::{collections::HashMap, fs::File};
+
+use crate::{rules::Rules, Action, Game};
+
+/// State of the game up to names: the sorted multiset of (team, power, visibility)
+/// of all players. Names only matter for writing actions, so states with equal keys
+/// have the same futures. Shields and super players which can be split tie the future
+/// to names, so then the key also lists every player, shield and part by name
+type StateKey = (Vec<(u64, u64, bool)>, Vec<String>);
+
+fn state_key(game: &Game) -> StateKey {
+    let mut players: Vec<(u64, u64, bool)> = game
+        .players
+        .values()
+        .map(|player| (player.team_number, player.power, player.is_visible))
+        .collect();
+    players.sort();
+
+    let mut names = Vec::new();
+    if !game.shields.is_empty() || !game.lineage.is_empty() {
+        names.extend(game.players.values().map(|player| {
+            format!(
+                "{} {} {} {}",
+                player.name, player.team_number, player.power, player.is_visible
+            )
+        }));
+        names.extend(
+            game.shields
+                .iter()
+                .map(|(protected, shield)| format!("shield {} {}", protected, shield)),
+        );
+        names.extend(game.lineage.iter().map(|(name, parts)| {
+            let parts: Vec<String> = parts
+                .iter()
+                .map(|part| format!("{} {}", part.name, part.power))
+                .collect();
+            format!("{} = {}", name, parts.join(", "))
+        }));
+    }
+    names.sort();
+    (players, names)
+}
+
+/// Upper bound of the team power after `remaining` actions. A single action raises
+/// the team power at most by the power of its strongest player and at most doubles
+/// that player (an attack; heal only adds one because of ceiling, the other actions
+/// never add to the team).
+/// Powers are capped by 1000, and a team without power can never gain it again
+fn power_upper_bound(powers: &[u64], remaining: usize) -> u64 {
+    let mut total: u64 = powers.iter().sum();
+    let mut strongest = powers.iter().copied().max().unwrap_or(0);
+    for _ in 0..remaining {
+        if strongest == 0 {
+            break;
+        }
+        total += strongest;
+        strongest = 1000.min(2 * strongest);
+    }
+    total.min(1000 * powers.len() as u64)
+}
+
+/// Lower bound of the team power after `remaining` actions. A single action takes power
+/// from at most two players (a super with MAX_PARTNERS partners loses at most the power
+/// of two of them), so at worst the strongest `2 * remaining` players lose everything
+fn power_lower_bound(powers: &[u64], remaining: usize) -> u64 {
+    let mut sorted = powers.to_vec();
+    sorted.sort_unstable();
+    let kept = sorted.len().saturating_sub(2 * remaining);
+    sorted[..kept].iter().sum()
+}
+
+/// Checks whether the team can not win in `remaining` actions even in the best case
+fn hopeless(game: &Game, team: usize, remaining: usize) -> bool {
+    let mut powers = vec![Vec::new(); game.teams.len()];
+    for player in game.players.values() {
+        powers[player.team_number as usize].push(player.power);
+    }
+
+    let best = power_upper_bound(&powers[team], remaining);
+    powers
+        .iter()
+        .enumerate()
+        .any(|(other, powers)| other != team && power_lower_bound(powers, remaining) >= best)
+}
+
+/// Depth limited search. `failed` remembers states which were already searched
+/// with at least the given amount of remaining actions without success
+fn search(
+    game: &Game,
+    team: usize,
+    remaining: usize,
+    failed: &mut HashMap<StateKey, usize>,
+    path: &mut Vec<Action>,
+) -> bool {
+    if game.get_winner_index() == Some(team) {
+        return true;
+    }
+    if remaining == 0 || hopeless(game, team, remaining) {
+        return false;
+    }
+
+    let key = state_key(game);
+    if failed.get(&key).is_some_and(|&depth| depth >= remaining) {
+        return false;
+    }
+
+    for action in game.legal_actions() {
+        let mut next = game.clone();
+        next.perform(&action).unwrap();
+        path.push(action);
+        if search(&next, team, remaining - 1, failed, path) {
+            return true;
+        }
+        path.pop();
+    }
+
+    failed.insert(key, remaining);
+    false
+}
+
+/// Finds the shortest sequence of at most `max_actions` actions without warnings
+/// after which the team's magician is chosen
+///
+/// Returns the sequence, or None if the team can not win in time
+pub fn can_win(game: &Game, team: usize, max_actions: usize) -> Option<Vec<Action>> {
+    // Iterative deepening gives the shortest witness
+    for depth in 0..=max_actions {
+        let mut path = Vec::new();
+        if search(game, team, depth, &mut HashMap::new(), &mut path) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// `solve <file> <magician> <k>` command. Plays the file and checks whether the magician
+/// can still be chosen after at most k more actions, printing a witness if so
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage = "Usage: solve <file> <magician> <k>";
+    let [path, magician, depth] = args else {
+        return Err(usage.to_string());
+    };
+    let depth: usize = depth.parse().map_err(|_| usage)?;
+
+    let mut input = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let game = Game::run(&mut input, &Rules::default()).map_err(|e| format!("{}: {}", path, e))?;
+    let team = game
+        .teams
+        .iter()
+        .position(|name| name == magician)
+        .ok_or_else(|| format!("Unknown magician `{}`", magician))?;
+
+    match can_win(&game, team, depth) {
+        Some(actions) => {
+            println!("yes");
+            for action in actions {
+                println!("{}", action);
+            }
+        }
+        None => println!("no"),
+    }
+    Ok(())
+}
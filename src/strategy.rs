@@ -0,0 +1,241 @@
+use std::fs::File;
+
+use crate::{random::Random, scenario::Scenario, Action, Game};
+
+/// Bot choosing actions for a single team
+pub trait Strategy {
+    /// Chooses the next action of a player of the team, or None to pass the turn
+    fn choose(&mut self, game: &Game, team: usize) -> Option<Action>;
+}
+
+/// Actions of the team's players which would be performed without a warning
+pub fn team_actions(game: &Game, team: usize) -> Vec<Action> {
+    game.legal_actions()
+        .into_iter()
+        .filter(|action| game.players[action.actor()].team_number == team as u64)
+        .collect()
+}
+
+/// Power of the team minus the power of the strongest other team
+pub fn margin(game: &Game, team: usize) -> i64 {
+    let powers = game.team_powers();
+    let best_other = powers
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != team)
+        .map(|(_, power)| *power)
+        .max()
+        .unwrap_or(0);
+    powers[team] as i64 - best_other as i64
+}
+
+/// Margin of the team after the action would be performed
+fn margin_after(game: &Game, team: usize, action: &Action) -> i64 {
+    let mut copy = game.clone();
+    copy.perform(action).unwrap();
+    margin(&copy, team)
+}
+
+/// The first of the actions with the largest key
+fn best_by_key(actions: &[Action], key: impl Fn(&Action) -> i64) -> Option<&Action> {
+    let mut best: Option<(&Action, i64)> = None;
+    for action in actions {
+        let value = key(action);
+        if best.is_none_or(|(_, best_value)| value > best_value) {
+            best = Some((action, value));
+        }
+    }
+    best.map(|(action, _)| action)
+}
+
+/// Plays a uniformly random legal action
+pub struct RandomBot {
+    random: Random,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            random: Random::new(seed),
+        }
+    }
+}
+
+impl Strategy for RandomBot {
+    fn choose(&mut self, game: &Game, team: usize) -> Option<Action> {
+        let actions = team_actions(game, team);
+        if actions.is_empty() {
+            return None;
+        }
+        Some(self.random.pick(&actions).clone())
+    }
+}
+
+/// Plays the attack which improves the margin the most.
+/// Without a useful attack it reveals a hidden player, so it can attack later
+pub struct GreedyBot;
+
+impl Strategy for GreedyBot {
+    fn choose(&mut self, game: &Game, team: usize) -> Option<Action> {
+        let actions = team_actions(game, team);
+        let current = margin(game, team);
+
+        let attacks: Vec<Action> = actions
+            .iter()
+            .filter(|action| matches!(action, Action::Attack(_, _)))
+            .cloned()
+            .collect();
+        if let Some(attack) = best_by_key(&attacks, |action| margin_after(game, team, action)) {
+            if margin_after(game, team, attack) > current {
+                return Some(attack.clone());
+            }
+        }
+
+        actions.into_iter().find(|action| match action {
+            Action::FlipVisibility(actor) => !game.players[actor].is_visible,
+            _ => false,
+        })
+    }
+}
+
+/// Heals frozen teammates and hides everybody else, so attackers freeze themselves
+pub struct TurtleBot;
+
+impl Strategy for TurtleBot {
+    fn choose(&mut self, game: &Game, team: usize) -> Option<Action> {
+        let actions = team_actions(game, team);
+
+        let heals: Vec<Action> = actions
+            .iter()
+            .filter(|action| match action {
+                Action::Heal(_, target) => game.players[target].power == 0,
+                _ => false,
+            })
+            .cloned()
+            .collect();
+        if let Some(heal) = best_by_key(&heals, |action| game.players[action.actor()].power as i64)
+        {
+            return Some(heal.clone());
+        }
+
+        actions.into_iter().find(|action| match action {
+            Action::FlipVisibility(actor) => game.players[actor].is_visible,
+            _ => false,
+        })
+    }
+}
+
+/// Merges the strongest pair of teammates whenever possible and plays greedy otherwise
+pub struct MergeBot;
+
+impl Strategy for MergeBot {
+    fn choose(&mut self, game: &Game, team: usize) -> Option<Action> {
+        let supers: Vec<Action> = team_actions(game, team)
+            .into_iter()
+            .filter(|action| matches!(action, Action::Super(_, _)))
+            .collect();
+        let power = |name: &str| game.players[name].power as i64;
+        if let Some(merge) = best_by_key(&supers, |action| {
+            action.players().iter().map(|name| power(name)).sum()
+        }) {
+            return Some(merge.clone());
+        }
+
+        GreedyBot.choose(game, team)
+    }
+}
+
+/// Creates a built-in strategy by its name
+pub fn strategy_by_name(name: &str, seed: u64) -> Option<Box<dyn Strategy>> {
+    Some(match name {
+        "random" => Box::new(RandomBot::new(seed)),
+        "greedy" => Box::new(GreedyBot),
+        "turtle" => Box::new(TurtleBot),
+        "merge" => Box::new(MergeBot),
+        _ => return None,
+    })
+}
+
+/// Plays a match between strategies, one per team. Teams take turns in the order
+/// of their numbers. The match ends after `max_actions` actions or when every team
+/// passed in a row
+///
+/// Returns the roster with the played actions, so the match can be replayed with `Game::run`
+pub fn play_match(
+    roster: &Scenario,
+    strategies: &mut [Box<dyn Strategy>],
+    max_actions: usize,
+) -> Scenario {
+    let mut game = roster.roster();
+    let mut log = Scenario {
+        actions: Vec::new(),
+        ..roster.clone()
+    };
+
+    let mut passes = 0;
+    let mut team = 0;
+    while log.actions.len() < max_actions && passes < strategies.len() {
+        match strategies[team].choose(&game, team) {
+            // Actions with missing players would make the log invalid, they count as a pass
+            Some(action) if game.perform(&action).is_ok() => {
+                log.actions.push(action);
+                passes = 0;
+            }
+            _ => passes += 1,
+        }
+        team = (team + 1) % strategies.len();
+    }
+    log
+}
+
+/// `bots <roster file> <strategy>... [--actions K] [--seed S]` command.
+/// Plays a match between built-in strategies (one per team, or one for all teams)
+/// and prints the log in the input format
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage =
+        "Usage: bots <roster file> (random|greedy|turtle|merge)... [--actions K] [--seed S]";
+    let path = args.first().ok_or(usage)?;
+
+    let mut names = Vec::new();
+    let mut max_actions = 100;
+    let mut seed = 0;
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--actions" | "--seed" => {
+                let value = rest
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .ok_or(usage)?;
+                if arg == "--actions" {
+                    max_actions = value as usize;
+                } else {
+                    seed = value;
+                }
+            }
+            _ => names.push(arg.as_str()),
+        }
+    }
+
+    let mut input = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let roster = Scenario::read_roster(&mut input).map_err(|e| format!("{}: {}", path, e))?;
+    let teams = roster.teams.len();
+    if names.len() == 1 {
+        names = vec![names[0]; teams];
+    }
+    if names.len() != teams {
+        return Err(format!("Expected 1 or {} strategies", teams));
+    }
+
+    let mut strategies = names
+        .iter()
+        .enumerate()
+        .map(|(team, name)| {
+            strategy_by_name(name, Random::stream(seed, team as u64).next_u64())
+                .ok_or_else(|| format!("Unknown strategy `{}`", name))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    print!("{}", play_match(&roster, &mut strategies, max_actions));
+    Ok(())
+}
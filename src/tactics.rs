@@ -0,0 +1,106 @@
+use crate::{Action, Game, INVALID_INPUT};
+
+/// Power a player pays to revive a teammate
+pub const REVIVE_COST: u64 = 150;
+/// Power a revived player comes back with
+pub const REVIVE_POWER: u64 = 100;
+
+impl Game {
+    /// Shield taking the attack instead of its target. A shield does not protect
+    /// from its own attacks and is gone once either player is merged
+    ///
+    /// Returns None if the action is not an attack on a shielded player
+    /// or the shield is not in the game
+    pub fn shield_for(&self, action: &Action) -> Option<String> {
+        let Action::Attack(actor, target) = action else {
+            return None;
+        };
+        self.shields
+            .get(target)
+            .filter(|shield| *shield != actor && self.players.contains_key(*shield))
+            .cloned()
+    }
+
+    /// Unfrozen teammates of the player, sorted by name
+    pub fn teammates(&self, name: &str) -> Vec<String> {
+        let Some(player) = self.players.get(name) else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = self
+            .players
+            .values()
+            .filter(|other| {
+                other.team_number == player.team_number && other.name != name && other.power > 0
+            })
+            .map(|other| other.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Names of the players the action changes if it is performed, the acting player first:
+    /// an attack on a shielded player changes the shield instead,
+    /// a sacrifice changes every unfrozen teammate and a split gives the parts of the player
+    pub fn affected(&self, action: &Action) -> Vec<String> {
+        let mut names: Vec<String> = match (action, self.shield_for(action)) {
+            (Action::Sacrifice(actor), _) => [actor.clone()]
+                .into_iter()
+                .chain(self.teammates(actor))
+                .collect(),
+            (Action::Split(actor), _) => self.parts(actor).unwrap_or_else(|| vec![actor.clone()]),
+            (_, Some(shield)) => vec![action.actor().to_string(), shield],
+            _ => action
+                .players()
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        };
+        names.dedup();
+        names
+    }
+
+    /// Performs shield, revive, transfer and sacrifice actions:
+    /// - `shield A B`: B takes the next attack aimed at A
+    /// - `revive A B`: A pays REVIVE_COST and frozen B comes back with REVIVE_POWER
+    /// - `transfer A B N`: A gives exactly N power to B, B gets at most 1000
+    /// - `sacrifice A`: A is frozen and its power is split equally between unfrozen teammates,
+    ///   the remainder is lost
+    /// - `split S`: the super player S is replaced with the players it was made of
+    ///
+    /// Returns the same values as `perform`
+    pub fn perform_tactic(
+        &mut self,
+        action: &Action,
+    ) -> Result<Option<&'static str>, &'static str> {
+        if let Some(warning) = self.rule_warning(action).ok_or(INVALID_INPUT)? {
+            return Ok(Some(warning));
+        }
+
+        match action {
+            Action::Shield(actor, target) => {
+                self.shields.insert(actor.clone(), target.clone());
+            }
+            Action::Revive(actor, target) => {
+                self.players.get_mut(actor).unwrap().power -= REVIVE_COST;
+                self.players.get_mut(target).unwrap().power = REVIVE_POWER;
+            }
+            Action::Transfer(actor, target, amount) => {
+                self.players.get_mut(actor).unwrap().power -= amount;
+                let target = self.players.get_mut(target).unwrap();
+                target.power = 1000.min(target.power + amount);
+            }
+            Action::Split(actor) => self.split(actor),
+            Action::Sacrifice(actor) => {
+                let teammates = self.teammates(actor);
+                let power = std::mem::take(&mut self.players.get_mut(actor).unwrap().power);
+                for name in &teammates {
+                    let teammate = self.players.get_mut(name).unwrap();
+                    teammate.power = 1000.min(teammate.power + power / teammates.len() as u64);
+                }
+            }
+            // The task actions are performed by `perform`
+            _ => {}
+        }
+        Ok(None)
+    }
+}
@@ -1,8 +1,41 @@
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::Read};
+    use std::{
+        collections::BTreeSet,
+        fs::File,
+        io::{pipe, BufRead, BufReader, Read, Write},
+        net::{SocketAddr, TcpListener, TcpStream},
+        sync::Arc,
+        thread,
+    };
 
-    use crate::solution;
+    use crate::{
+        effects::Duration,
+        election::{elect, ElectionConfig},
+        energy::Energy,
+        engine::{referee, Connection, Engine},
+        format::canonical,
+        generate::{generate, generate_text, GeneratorConfig, InvalidKind},
+        history::{append, format_timestamp, read, Filter, Record},
+        json::Json,
+        lint::lint,
+        minimax::minimax,
+        minimize::{minimize, Predicate},
+        multiplayer::host,
+        random::Random,
+        ratings::{contributions, Ratings, INITIAL_RATING},
+        rules::Rules,
+        scenario::Scenario,
+        server::{serve, Server},
+        simulate::{confidence_interval, simulate, SimulationConfig},
+        solution, solution_with,
+        solver::can_win,
+        strategy::{play_match, strategy_by_name, team_actions, Strategy, TurtleBot},
+        tournament::{read_entries, run_tournament, Format, TournamentConfig},
+        turns::TurnPolicy,
+        view::Fog,
+        Action, Game,
+    };
 
     fn as_string(file: &str) -> String {
         let mut buffer = String::new();
@@ -104,4 +137,1295 @@ mod tests {
             as_string("tests/example10_solution.txt")
         )
     }
+
+    #[test]
+    fn lint_1() {
+        let findings = lint(&mut File::open("tests/lint1_input.txt").unwrap()).unwrap();
+        let report: String = findings.iter().map(|f| format!("{}\n", f)).collect();
+        assert_eq!(report, as_string("tests/lint1_solution.txt"))
+    }
+
+    #[test]
+    fn lint_invalid_input() {
+        assert!(lint(&mut File::open("tests/example5_input.txt").unwrap()).is_err());
+        assert!(lint(&mut File::open("tests/example1_input.txt").unwrap()).is_ok());
+
+        // The game of a single team is never decided
+        let text = "1\nHarry\n2\nRon\n0\n300\nTrue\nHermione\n0\n100\nTrue\nheal Ron Hermione\n\
+                    heal Hermione Ron\n";
+        assert_eq!(lint(&mut text.as_bytes()), Ok(Vec::new()));
+
+        // A team with an unfrozen player can still freeze itself into a tie
+        let text = "2\nHarry\nDraco\n2\nRon\n0\n300\nTrue\nVincent\n1\n0\nTrue\n\
+                    flip_visibility Ron\nheal Vincent Vincent\n";
+        let findings = lint(&mut text.as_bytes()).unwrap();
+        assert!(findings
+            .iter()
+            .all(|finding| finding.category != "after-decided"));
+        let text = "2\nHarry\nDraco\n2\nRon\n0\n0\nTrue\nVincent\n1\n0\nTrue\n\
+                    attack Ron Vincent\n";
+        let categories: Vec<&str> = lint(&mut text.as_bytes())
+            .unwrap()
+            .iter()
+            .map(|finding| finding.category)
+            .collect();
+        assert_eq!(
+            categories,
+            ["idle-player", "after-decided", "frozen-player"]
+        );
+    }
+
+    #[test]
+    fn fmt_1() {
+        let formatted = canonical(&as_string("tests/fmt1_input.txt")).unwrap();
+        assert_eq!(formatted, as_string("tests/fmt1_solution.txt"));
+        // Canonical files are left as they are
+        assert_eq!(canonical(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn tolerant_input() {
+        let tolerant = Rules {
+            tolerant_input: true,
+            ..Rules::default()
+        };
+        solution("tests/fmt1_input.txt", "tests/fmt1_strict_output.txt");
+        assert_eq!(
+            as_string("tests/fmt1_strict_output.txt"),
+            "Invalid inputs\n"
+        );
+
+        solution_with("tests/fmt1_input.txt", "tests/fmt1_output.txt", &tolerant);
+        solution("tests/fmt1_solution.txt", "tests/fmt1_canonical_output.txt");
+        assert_eq!(
+            as_string("tests/fmt1_output.txt"),
+            as_string("tests/fmt1_canonical_output.txt")
+        )
+    }
+
+    #[test]
+    fn minimize_1() {
+        let predicate = Predicate::Contains("This player can't play".to_string());
+        let minimized = minimize(&as_string("tests/example3_input.txt"), &predicate).unwrap();
+        assert_eq!(minimized, as_string("tests/minimize1_solution.txt"));
+
+        let predicate = Predicate::Verdict("It's a tie".to_string());
+        assert!(minimize(&as_string("tests/example3_input.txt"), &predicate).is_err());
+    }
+
+    #[test]
+    fn minimize_rejected() {
+        let minimized = minimize(&as_string("tests/example8_input.txt"), &Predicate::Rejected);
+        assert_eq!(minimized.unwrap(), "11\n");
+    }
+
+    #[test]
+    fn random_is_reproducible() {
+        // Reference value of SplitMix64 for the zero seed
+        assert_eq!(Random::new(0).next_u64(), 0xE220_A839_7B1D_CDAF);
+    }
+
+    #[test]
+    fn generate_valid() {
+        for seed in 0..20 {
+            let config = GeneratorConfig {
+                seed,
+                teams: 4,
+                players: 30,
+                actions: 300,
+                ..GeneratorConfig::default()
+            };
+            let text = generate_text(&config);
+            assert_eq!(text, generate_text(&config));
+            assert!(Game::run(&mut text.as_bytes(), &Rules::default()).is_ok());
+        }
+
+        let first = GeneratorConfig::default();
+        let second = GeneratorConfig {
+            seed: 1,
+            ..GeneratorConfig::default()
+        };
+        assert_ne!(generate_text(&first), generate_text(&second));
+    }
+
+    #[test]
+    fn generate_invalid() {
+        for kind in InvalidKind::ALL {
+            let config = GeneratorConfig {
+                invalid: Some(kind),
+                ..GeneratorConfig::default()
+            };
+            let text = generate_text(&config);
+            assert!(
+                Game::run(&mut text.as_bytes(), &Rules::default()).is_err(),
+                "{} was accepted",
+                kind.name()
+            );
+        }
+    }
+
+    #[test]
+    fn simulate_is_reproducible() {
+        let roster = Game::run(
+            &mut File::open("tests/example6_input.txt").unwrap(),
+            &Rules::default(),
+        )
+        .unwrap();
+        let config = |threads| SimulationConfig {
+            seed: 42,
+            games: 300,
+            actions: 10,
+            threads,
+        };
+
+        let estimate = simulate(&roster, &config(1));
+        assert_eq!(estimate, simulate(&roster, &config(3)));
+        assert_eq!(estimate.games, 300);
+        assert_eq!(estimate.wins.iter().sum::<u64>() + estimate.ties, 300);
+    }
+
+    #[test]
+    fn simulate_frozen_team_never_wins() {
+        let text =
+            "2\nHarry\nDraco\n3\nRon\n0\n500\nTrue\nGoyle\n1\n0\nTrue\nCrabbe\n1\n0\nFalse\n";
+        let roster = Game::run(&mut text.as_bytes(), &Rules::default()).unwrap();
+        let config = SimulationConfig {
+            games: 1000,
+            ..SimulationConfig::default()
+        };
+        let estimate = simulate(&roster, &config);
+        assert_eq!(estimate.wins[1], 0);
+        assert!(estimate.wins[0] > 0);
+
+        let (low, high) = confidence_interval(50, 100);
+        assert!(low < 0.5 && 0.5 < high);
+    }
+
+    #[test]
+    fn moves_agree_with_engine() {
+        let mut verbs = BTreeSet::new();
+        for seed in 0..10 {
+            let scenario = generate(&GeneratorConfig {
+                seed,
+                teams: 3,
+                players: 7,
+                visibility_ratio: 0.6,
+                actions: 40,
+                ..GeneratorConfig::default()
+            });
+            let mut game = Game::new(scenario.teams.clone(), scenario.players.clone());
+
+            for action in &scenario.actions {
+                for (candidate, warning) in game.classify_actions() {
+                    let mut copy = game.clone();
+                    assert_eq!(copy.perform(&candidate), Ok(warning), "{}", candidate);
+                    if warning.is_none() {
+                        let text = candidate.to_string();
+                        let verb = match &candidate {
+                            Action::Super(_, partners) if partners.len() > 1 => "super+",
+                            _ => text.split(' ').next().unwrap(),
+                        };
+                        verbs.insert(verb.to_string());
+                    }
+                }
+                game.perform(action).unwrap();
+            }
+        }
+        // Every kind of action is enumerated and performed somewhere
+        assert_eq!(
+            verbs.into_iter().collect::<Vec<_>>(),
+            [
+                "attack",
+                "flip_visibility",
+                "heal",
+                "revive",
+                "sacrifice",
+                "shield",
+                "split",
+                "super",
+                "super+",
+                "transfer",
+            ]
+        );
+    }
+
+    #[test]
+    fn legal_actions() {
+        let text =
+            "2\nHarry\nDraco\n3\nRon\n0\n500\nTrue\nGinny\n0\n0\nTrue\nGoyle\n1\n300\nFalse\n";
+        let game = Game::run(&mut text.as_bytes(), &Rules::default()).unwrap();
+        let legal: Vec<String> = game.legal_actions().iter().map(Action::to_string).collect();
+        assert_eq!(
+            legal,
+            [
+                "flip_visibility Goyle",
+                "flip_visibility Ron",
+                "sacrifice Ron",
+                "attack Ron Ginny",
+                "heal Ron Ginny",
+                "super Ron Ginny",
+                "shield Ron Ginny",
+                "revive Ron Ginny",
+                "transfer Ron Ginny 1",
+                "transfer Ron Ginny 250",
+                "transfer Ron Ginny 500",
+                "attack Ron Goyle",
+                "attack Ron Ron",
+            ]
+        );
+    }
+
+    #[test]
+    fn bot_matches_replay() {
+        let names = ["random", "greedy", "turtle", "merge"];
+        for seed in 0..8 {
+            let roster = Scenario {
+                actions: Vec::new(),
+                ..generate(&GeneratorConfig {
+                    seed,
+                    teams: 4,
+                    players: 12,
+                    ..GeneratorConfig::default()
+                })
+            };
+            let mut strategies: Vec<Box<dyn Strategy>> = names
+                .iter()
+                .map(|name| strategy_by_name(name, seed).unwrap())
+                .collect();
+            let log = play_match(&roster, &mut strategies, 60);
+            assert!(log.actions.len() <= 60);
+
+            let mut game = roster.roster();
+            for action in &log.actions {
+                game.perform(action).unwrap();
+            }
+            let replayed = Game::run(&mut log.to_string().as_bytes(), &Rules::default()).unwrap();
+            assert_eq!(replayed.team_powers(), game.team_powers());
+            assert_eq!(replayed.verdict(), game.verdict());
+        }
+    }
+
+    #[test]
+    fn turtle_heals_then_hides() {
+        let text = "1\nHarry\n2\nRon\n0\n500\nTrue\nGinny\n0\n0\nTrue\n";
+        let mut game = Game::run(&mut text.as_bytes(), &Rules::default()).unwrap();
+        let action = TurtleBot.choose(&game, 0).unwrap();
+        assert_eq!(action, Action::Heal("Ron".to_string(), "Ginny".to_string()));
+
+        game.perform(&action).unwrap();
+        let action = TurtleBot.choose(&game, 0).unwrap();
+        assert!(matches!(action, Action::FlipVisibility(_)));
+    }
+
+    #[test]
+    fn solver_finds_shortest_witness() {
+        let roster = Scenario::read_roster(&mut File::open("tests/example1_input.txt").unwrap())
+            .unwrap()
+            .roster();
+        // Ron's player is the third strongest, so two stronger players have to fall first
+        assert_eq!(can_win(&roster, 2, 1), None);
+        let witness = can_win(&roster, 2, 4).unwrap();
+        assert_eq!(witness.len(), 2);
+
+        let mut game = roster.clone();
+        for action in &witness {
+            assert_eq!(game.perform(action), Ok(None));
+        }
+        assert_eq!(game.get_winner_index(), Some(2));
+    }
+
+    #[test]
+    fn solver_prunes_frozen_team() {
+        let game = Game::run(
+            &mut File::open("tests/example1_input.txt").unwrap(),
+            &Rules::default(),
+        )
+        .unwrap();
+        // Harry's only player is frozen, so his team can never gain power
+        assert_eq!(can_win(&game, 0, 50), None);
+        assert_eq!(can_win(&game, 1, 0), Some(Vec::new()));
+    }
+
+    /// Plain minimax without pruning, the alpha-beta search should agree with it
+    fn reference_minimax(game: &Game, team: usize, opponent: usize, depth: usize) -> i64 {
+        if depth == 0 {
+            let powers = game.team_powers();
+            return powers[team] as i64 - powers[opponent] as i64;
+        }
+        let actions = team_actions(game, team);
+        if actions.is_empty() {
+            return -reference_minimax(game, opponent, team, depth - 1);
+        }
+        actions
+            .iter()
+            .map(|action| {
+                let mut next = game.clone();
+                next.perform(action).unwrap();
+                -reference_minimax(&next, opponent, team, depth - 1)
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn minimax_agrees_with_plain_search() {
+        for seed in 0..6 {
+            let scenario = generate(&GeneratorConfig {
+                seed,
+                teams: 2,
+                players: 4,
+                actions: 3,
+                ..GeneratorConfig::default()
+            });
+            let mut game = scenario.roster();
+            for action in &scenario.actions {
+                game.perform(action).unwrap();
+            }
+
+            let line = minimax(&game, 0, 1, 3);
+            assert_eq!(line.evaluation, reference_minimax(&game, 0, 1, 3));
+            assert_eq!(line.variation.len(), 3);
+
+            // The principal variation leads to the evaluated margin
+            let mut end = game.clone();
+            for action in line.variation.iter().flatten() {
+                assert_eq!(end.perform(action), Ok(None));
+            }
+            let powers = end.team_powers();
+            assert_eq!(powers[0] as i64 - powers[1] as i64, line.evaluation);
+        }
+    }
+
+    #[test]
+    fn tournament_formats() {
+        let entries =
+            read_entries(&mut File::open("tests/tournament1_input.txt").unwrap()).unwrap();
+        assert_eq!(entries.len(), 4);
+
+        for format in [
+            Format::RoundRobin,
+            Format::SingleElimination,
+            Format::DoubleElimination,
+            Format::Swiss,
+        ] {
+            let config = TournamentConfig {
+                format,
+                win_points: 2,
+                tie_points: 1,
+                ..TournamentConfig::default()
+            };
+            let result = run_tournament(&entries, &config);
+            // Albus's team is much stronger than everybody else
+            assert_eq!(result.club_head, 0);
+            assert_eq!(result.standings[0].team, 0);
+
+            // A win gives 2 points to one team, a tie gives 1 point to both
+            let points = 2 * result.matches.len() as u64;
+            assert_eq!(
+                result.standings.iter().map(|s| s.points).sum::<u64>(),
+                points
+            );
+            if format == Format::RoundRobin {
+                assert_eq!(result.matches.len(), 6);
+            }
+        }
+    }
+
+    #[test]
+    fn tournament_rejects_repeated_names() {
+        let text = "2\nAlbus\ngreedy\n1\nHarry\n10\nTrue\nGellert\nrandom\n1\nHarry\n20\nTrue\n";
+        assert!(read_entries(&mut text.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn ratings_contributions() {
+        let scenario = Scenario::read(
+            &mut File::open("tests/example1_input.txt").unwrap(),
+            &Rules::default(),
+        )
+        .unwrap();
+        let (_, contributions) = contributions(&scenario).unwrap();
+        let summary: Vec<(&str, u64, bool)> = contributions
+            .iter()
+            .map(|c| (c.name.as_str(), c.freezes, c.survived))
+            .collect();
+        // Alaa was hidden, so Munir froze himself attacking her
+        assert_eq!(
+            summary,
+            vec![
+                ("Munir", 2, false),
+                ("Alaa", 1, true),
+                ("Mohamad", 0, false),
+                ("Alexandr", 1, false),
+                ("Mikhail", 0, false)
+            ]
+        );
+
+        let mut ratings = Ratings::default();
+        ratings.record("first", &scenario).unwrap();
+        ratings.record("second", &scenario).unwrap();
+        let leaderboard = ratings.leaderboard();
+        assert_eq!(leaderboard[0].0, "Alaa");
+        assert!(leaderboard[0].1 > INITIAL_RATING);
+        assert!(ratings.rating("Munir") > ratings.rating("Mohamad"));
+        assert_eq!(ratings.players["Alaa"].len(), 2);
+
+        let stored = ratings.to_string();
+        assert_eq!(
+            Ratings::read(&mut stored.as_bytes()).unwrap().to_string(),
+            stored
+        );
+        assert!(Ratings::read(&mut "Alaa 1500.00 0 True\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn history_survives_torn_writes() {
+        let path = "tests/history1_output.txt";
+        let _ = std::fs::remove_file(path);
+
+        let scenario = as_string("tests/example1_input.txt");
+        let game = Game::run(&mut scenario.as_bytes(), &Rules::default());
+        let mut record = Record::new(scenario.clone(), &game);
+        record.timestamp = 1_700_000_000;
+        assert_eq!(record.verdict, "The chosen wizard is Hermione");
+        assert_eq!(record.events[0], "flip_visibility Alaa: Alaa 500 False");
+        assert_eq!(
+            record.events[4],
+            "attack Munir Alaa: Munir 0 True, Alaa 500 False"
+        );
+        append(path, &record).unwrap();
+
+        let tie = Record {
+            timestamp: 0,
+            scenario: "2\r\nHarry\r\n".to_string(),
+            events: Vec::new(),
+            verdict: "Invalid inputs".to_string(),
+        };
+        append(path, &tie).unwrap();
+        assert_eq!(read(path).unwrap(), vec![record.clone(), tie.clone()]);
+
+        // An append interrupted by a power cut leaves a torn record at the end
+        let mut bytes = std::fs::read(path).unwrap();
+        bytes.extend_from_slice(b"record 120 00ff\n1700");
+        std::fs::write(path, bytes).unwrap();
+        assert_eq!(read(path).unwrap().len(), 2);
+        append(path, &record).unwrap();
+        assert_eq!(
+            read(path).unwrap(),
+            vec![record.clone(), tie.clone(), record.clone()]
+        );
+
+        // A record damaged in the middle is never cut off with the records after it
+        let mut bytes = std::fs::read(path).unwrap();
+        let middle = bytes.len() / 2;
+        bytes[middle] ^= 1;
+        std::fs::write(path, &bytes).unwrap();
+        assert!(append(path, &tie).is_err());
+        assert_eq!(std::fs::read(path).unwrap(), bytes);
+        bytes[middle] ^= 1;
+        std::fs::write(path, &bytes).unwrap();
+
+        let filter = Filter {
+            player: Some("Munir".to_string()),
+            ..Filter::default()
+        };
+        assert!(filter.matches(&record));
+        let filter = Filter {
+            magician: Some("Draco".to_string()),
+            ties: true,
+            ..Filter::default()
+        };
+        assert!(!filter.matches(&record));
+
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14 22:13:20 UTC");
+    }
+
+    #[test]
+    fn election_runoffs_between_tied_teams() {
+        let roster = generate(&GeneratorConfig {
+            seed: 3,
+            teams: 3,
+            players: 6,
+            actions: 0,
+            ..GeneratorConfig::default()
+        });
+        let config = ElectionConfig {
+            games: 2,
+            max_actions: 8,
+            seed: 26,
+            ..ElectionConfig::default()
+        };
+        let election = elect(&roster, &vec!["random".to_string(); 3], &config);
+        assert_eq!(
+            election,
+            elect(&roster, &vec!["random".to_string(); 3], &config)
+        );
+
+        // Every runoff is played only between the leaders of the previous round
+        for pair in election.rounds.windows(2) {
+            assert!(pair[0].leaders().len() > 1);
+            assert_eq!(pair[1].teams, pair[0].leaders());
+        }
+        assert_eq!(election.rounds.len(), 3);
+        assert_eq!(election.rounds[1].teams, vec![1, 2]);
+        assert_eq!(
+            election.rounds.last().unwrap().leaders(),
+            vec![election.club_head]
+        );
+        assert!(!election.by_order);
+
+        // Hiding turtles never win, so the tie is broken by the order of the teams
+        let text = "2\nHarry\nDraco\n2\nRon\n0\n100\nTrue\nVincent\n1\n100\nTrue\n";
+        let roster = Scenario::read_roster(&mut text.as_bytes()).unwrap();
+        let config = ElectionConfig {
+            games: 3,
+            max_runoffs: 2,
+            ..ElectionConfig::default()
+        };
+        let election = elect(&roster, &vec!["turtle".to_string(); 2], &config);
+        assert_eq!(election.rounds.len(), 3);
+        assert!(election.by_order);
+        assert_eq!(election.club_head, 0);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let text = r#"{"name":"Harry \"the boy\"\n","power":-5,"tags":[true,null,[]],"empty":{}}"#;
+        let value = Json::parse(text).unwrap();
+        assert_eq!(value.to_string(), text);
+        assert_eq!(value.get("power"), Some(&Json::Number(-5)));
+        assert_eq!(
+            Json::parse(" { \"a\" : \"\\u0041\" } ")
+                .unwrap()
+                .get("a")
+                .unwrap()
+                .as_str(),
+            Some("A")
+        );
+        assert!(Json::parse("{\"a\": 1.5}").is_none());
+        assert!(Json::parse("[1, 2").is_none());
+
+        // Deep nesting is rejected instead of overflowing the stack
+        assert!(Json::parse(&format!("{}{}", "[".repeat(64), "]".repeat(64))).is_some());
+        let deep = "[".repeat(200_000);
+        assert!(Json::parse(&deep).is_none());
+        assert_eq!(Server::default().handle("POST", "/games", &deep).0, 400);
+    }
+
+    /// Sends a single HTTP request and returns the status and the parsed body
+    fn http(address: SocketAddr, method: &str, path: &str, body: &Json) -> (u16, Json) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        let body = body.to_string();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, Json::parse(body).unwrap())
+    }
+
+    #[test]
+    fn server_plays_concurrent_games() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, Arc::new(Server::default())));
+
+        let roster = as_string("tests/example1_input.txt");
+        let roster = roster.lines().take(27).collect::<Vec<_>>().join("\n");
+        let clients: Vec<_> = (0..4)
+            .map(|_| {
+                let roster = roster.clone();
+                thread::spawn(move || {
+                    let request = Json::object([("scenario", roster.as_str().into())]);
+                    let (status, state) = http(address, "POST", "/games", &request);
+                    assert_eq!(status, 201);
+                    let id = match state.get("id") {
+                        Some(Json::Number(id)) => *id,
+                        _ => panic!("no id"),
+                    };
+
+                    let actions = as_string("tests/example1_input.txt");
+                    for line in actions.lines().skip(27) {
+                        let request = Json::object([("action", line.into())]);
+                        let path = format!("/games/{}/actions", id);
+                        assert_eq!(http(address, "POST", &path, &request).0, 200);
+                    }
+                    id
+                })
+            })
+            .collect();
+        let mut ids: Vec<i64> = clients.into_iter().map(|c| c.join().unwrap()).collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+
+        let (status, standings) = http(address, "GET", "/games/3/standings", &Json::Null);
+        assert_eq!(status, 200);
+        assert_eq!(
+            standings.get("verdict").and_then(Json::as_str),
+            Some("The chosen wizard is Hermione")
+        );
+        let (_, events) = http(address, "GET", "/games/2/events", &Json::Null);
+        let Some(Json::Array(events)) = events.get("events") else {
+            panic!("no events");
+        };
+        assert_eq!(events.len(), 5);
+
+        // A warning is the outcome, a missing player is an error which changes nothing
+        let heal = Json::object([("action", "heal Munir Munir".into())]);
+        let (status, outcome) = http(address, "POST", "/games/1/actions", &heal);
+        assert_eq!(status, 200);
+        assert_eq!(
+            outcome.get("warning").and_then(Json::as_str),
+            Some("This player is frozen")
+        );
+        let attack = Json::object([("action", "attack Munir Nobody".into())]);
+        assert_eq!(http(address, "POST", "/games/1/actions", &attack).0, 400);
+        let (_, state) = http(address, "GET", "/games/1", &Json::Null);
+        let Some(Json::Array(warnings)) = state.get("warnings") else {
+            panic!("no warnings");
+        };
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(http(address, "GET", "/games/9", &Json::Null).0, 404);
+    }
+
+    #[test]
+    fn game_is_shared_between_threads() {
+        fn shareable<T: Send + Sync>(_: &T) {}
+
+        let text = as_string("tests/example9_input.txt");
+        let game = Game::run(&mut text.as_bytes(), &Rules::default()).unwrap();
+        shareable(&game);
+
+        // Many threads read the game at once, then it is moved to another thread
+        let powers = game.team_powers();
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| assert_eq!(game.team_powers(), powers));
+            }
+        });
+        let verdict = thread::spawn(move || game.verdict()).join().unwrap();
+        assert_eq!(
+            Some(verdict.as_str()),
+            as_string("tests/example9_solution.txt").lines().last()
+        );
+    }
+
+    #[test]
+    fn engine_protocol() {
+        let roster = as_string("tests/example1_input.txt");
+        let roster: Vec<&str> = roster.lines().take(27).collect();
+        let commands = format!(
+            "isready\nstate\nroster\n{}\nend\naction attack Munir Mikhail\naction heal Munir Alaa\n\
+             action attack Nobody Alaa\nevents\ngo Harry\nverdict\nquit\nisready\n",
+            roster.join("\n")
+        );
+        let mut output = Vec::new();
+        Engine::new(strategy_by_name("greedy", 0).unwrap())
+            .run(commands.as_bytes(), &mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "readyok\n\
+             error No roster\n\
+             ok\n\
+             ok attack Munir Mikhail: Munir 1000 True, Mikhail 0 True\n\
+             warning Both players should be from the same team\n\
+             error Invalid inputs\n\
+             event attack Munir Mikhail: Munir 1000 True, Mikhail 0 True\n\
+             event heal Munir Alaa: Both players should be from the same team\n\
+             end\n\
+             bestmove attack Munir Alexandr\n\
+             verdict The chosen wizard is Harry\n"
+        );
+    }
+
+    #[test]
+    fn referee_matches_bot_match() {
+        let roster = generate(&GeneratorConfig {
+            seed: 5,
+            teams: 2,
+            players: 6,
+            actions: 0,
+            ..GeneratorConfig::default()
+        });
+
+        let mut engines = Vec::new();
+        for name in ["greedy", "turtle"] {
+            let (engine_input, commands) = pipe().unwrap();
+            let (answers, engine_output) = pipe().unwrap();
+            thread::spawn(move || {
+                let mut engine_output = engine_output;
+                Engine::new(strategy_by_name(name, 0).unwrap())
+                    .run(BufReader::new(engine_input), &mut engine_output)
+            });
+            engines.push(Connection {
+                input: Box::new(BufReader::new(answers)),
+                output: Box::new(commands),
+            });
+        }
+        let log = referee(&roster, &mut engines, 30).unwrap();
+
+        let mut bots = vec![
+            strategy_by_name("greedy", 0).unwrap(),
+            strategy_by_name("turtle", 0).unwrap(),
+        ];
+        assert_eq!(log, play_match(&roster, &mut bots, 30));
+        assert!(!log.actions.is_empty());
+    }
+
+    /// Client of a hosted game: sends a line and reads the given amount of lines
+    fn exchange(stream: &mut BufReader<TcpStream>, line: &str, answers: usize) -> Vec<String> {
+        writeln!(stream.get_mut(), "{}", line).unwrap();
+        (0..answers)
+            .map(|_| {
+                let mut answer = String::new();
+                stream.read_line(&mut answer).unwrap();
+                answer.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn multiplayer_enforces_own_team() {
+        let text = "2\nHarry\nDraco\n2\nRon\n0\n300\nTrue\nVincent\n1\n200\nTrue\n";
+        let roster = Scenario::read_roster(&mut text.as_bytes()).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || host(listener, &roster, Fog::Off));
+
+        let connect = || BufReader::new(TcpStream::connect(address).unwrap());
+        let (mut harry, mut draco) = (connect(), connect());
+        assert_eq!(
+            exchange(&mut harry, "action attack Ron Vincent", 1),
+            ["error Claim a team first"]
+        );
+        assert_eq!(exchange(&mut harry, "claim Harry", 1), ["ok"]);
+        assert_eq!(
+            exchange(&mut draco, "claim Harry", 1),
+            ["error Harry is already claimed"]
+        );
+        assert_eq!(exchange(&mut draco, "claim Draco", 1), ["ok"]);
+
+        assert_eq!(
+            exchange(&mut draco, "action attack Ron Vincent", 1),
+            ["error The player is not from your team"]
+        );
+        // The outcome is broadcast to both clients
+        let outcome = "outcome Harry attack Ron Vincent: Ron 400 True, Vincent 0 True";
+        assert_eq!(
+            exchange(&mut harry, "action attack Ron Vincent", 1),
+            [outcome]
+        );
+        assert_eq!(
+            exchange(&mut draco, "verdict", 2),
+            [outcome, "verdict The chosen wizard is Harry"]
+        );
+        assert_eq!(
+            exchange(&mut draco, "action heal Vincent Vincent", 1),
+            ["outcome Draco heal Vincent Vincent: This player is frozen"]
+        );
+        assert_eq!(
+            exchange(&mut harry, "state", 6),
+            [
+                "outcome Draco heal Vincent Vincent: This player is frozen",
+                "team Harry 400",
+                "team Draco 0",
+                "player Ron 0 400 True",
+                "player Vincent 1 0 True",
+                "end"
+            ]
+        );
+
+        // A team is free again after its client leaves
+        writeln!(draco.get_mut(), "quit").unwrap();
+        let mut other = connect();
+        let mut answer = exchange(&mut other, "claim Draco", 1);
+        while answer != ["ok"] {
+            answer = exchange(&mut other, "claim Draco", 1);
+        }
+    }
+
+    #[test]
+    fn fog_hides_invisible_enemies() {
+        let text = "2\nHarry\nDraco\n3\nRon\n0\n300\nTrue\nVincent\n1\n200\nFalse\n\
+                    Gregory\n1\n100\nTrue\nattack Ron Vincent\n";
+        let game = Game::run(&mut text.as_bytes(), &Rules::default()).unwrap();
+
+        let names = |team, fog| -> Vec<(String, Option<u64>)> {
+            game.view(team, fog)
+                .into_iter()
+                .map(|player| (player.name, player.power))
+                .collect()
+        };
+        let all = vec![
+            ("Gregory".to_string(), Some(100)),
+            ("Ron".to_string(), Some(0)),
+            ("Vincent".to_string(), Some(200)),
+        ];
+        assert_eq!(names(Some(0), Fog::Off), all);
+        assert_eq!(names(Some(1), Fog::Existence), all);
+        assert_eq!(names(Some(0), Fog::Power)[2], ("Vincent".to_string(), None));
+        assert_eq!(names(None, Fog::Existence), all[..2]);
+        assert_eq!(
+            game.view_lines(Some(0), Fog::Power)[..2],
+            ["team Harry 0", "team Draco ?"]
+        );
+        assert_eq!(
+            game.view_lines(Some(0), Fog::Existence)[..2],
+            ["team Harry 0", "team Draco ?"]
+        );
+
+        let attack = &game.events[0];
+        let seen = |team, fog| attack.seen_by(team, fog);
+        assert_eq!(
+            seen(Some(0), Fog::Power).unwrap(),
+            "attack Ron Vincent: Ron 0 True, the target was not visible"
+        );
+        assert_eq!(seen(Some(1), Fog::Power).unwrap(), attack.to_string());
+        assert_eq!(
+            seen(Some(0), Fog::Off).unwrap(),
+            "attack Ron Vincent: Ron 0 True, Vincent 200 False"
+        );
+        // Without existence a team sees only its own actions on hidden players
+        assert_eq!(seen(Some(0), Fog::Existence), seen(Some(0), Fog::Power));
+        assert_eq!(seen(None, Fog::Existence), None);
+
+        // Enemies' flips and warnings about hidden players are not seen at all
+        let (rules, _) = Rules::from_args(&["--invisibility=2:actions".to_string()]).unwrap();
+        let text = "2\nHarry\nDraco\n4\nRon\n0\n300\nTrue\nVincent\n1\n200\nTrue\n\
+                    Gregory\n1\n100\nTrue\nCrabbe\n1\n50\nTrue\nshield Gregory Crabbe\n\
+                    flip_visibility Crabbe\nattack Crabbe Ron\nattack Ron Gregory\n";
+        let game = Game::run(&mut text.as_bytes(), &rules).unwrap();
+        let seen: Vec<String> = game
+            .events
+            .iter()
+            .filter_map(|event| event.seen_by(Some(0), Fog::Existence))
+            .collect();
+        assert_eq!(
+            seen,
+            [
+                "shield Gregory Crabbe: Gregory 100 True, Crabbe 50 True",
+                "attack Ron Gregory: Ron 0 True, the target was not visible",
+                "reappear Crabbe: Crabbe 50 True",
+            ]
+        );
+        let power: Vec<String> = game
+            .events
+            .iter()
+            .filter_map(|event| event.seen_by(Some(0), Fog::Power))
+            .collect();
+        assert_eq!(power[1], "flip_visibility Crabbe: Crabbe hidden");
+        assert_eq!(power[2], "attack Crabbe Ron: This player can't play");
+    }
+
+    #[test]
+    fn rounds_resolve_simultaneously() {
+        let rounds = Rules {
+            simultaneous_rounds: true,
+            ..Rules::default()
+        };
+        let text = "2\nHarry\nDraco\n5\nRon\n0\n300\nTrue\nHermione\n0\n100\nTrue\n\
+                    Neville\n0\n50\nTrue\nVincent\n1\n200\nTrue\nGregory\n1\n100\nTrue\n\
+                    attack Ron Vincent\nattack Vincent Hermione\nheal Hermione Neville\n\
+                    super Ron Neville\nend_round\n\
+                    attack Gregory S_0\nflip_visibility S_0\nheal Vincent Gregory\n";
+        let game = Game::run(&mut text.as_bytes(), &rounds).unwrap();
+
+        let events: Vec<String> = game.events.iter().map(|event| event.to_string()).collect();
+        assert_eq!(
+            events,
+            [
+                "attack Ron Vincent: Ron 400 True, Vincent 0 True",
+                "attack Vincent Hermione: Vincent 0 True, Hermione 0 True",
+                "attack Vincent Hermione: conflict: Vincent is frozen in the round, its gain is lost",
+                "heal Hermione Neville: Hermione 0 True, Neville 100 True",
+                "super Ron Neville: S_0 500 True",
+                "attack Gregory S_0: Gregory 0 True, S_0 900 False",
+                "attack Gregory S_0: conflict: S_0 flips its visibility in the round, \
+                 the attack sees its visibility at the start of the round",
+                "flip_visibility S_0: S_0 900 False",
+                "heal Vincent Gregory: This player is frozen",
+            ]
+        );
+        assert_eq!(game.warnings, ["This player is frozen"]);
+
+        // Without rounds the same actions are played one by one
+        let game = Game::run(&mut text.as_bytes(), &Rules::default());
+        assert!(game.is_err());
+
+        // A round ended by the turn policy and by its end_round line is still one round
+        let rules = Rules {
+            turn_policy: TurnPolicy::OnePerPlayer,
+            energy: Energy::parse("heal:5,regen:3,max:10"),
+            ..rounds
+        };
+        let text = "2\nHarry\nDraco\n3\nRon\n0\n300\nTrue\nHermione\n0\n100\nTrue\n\
+                    Vincent\n1\n200\nTrue\nheal Ron Hermione\nheal Hermione Ron\n\
+                    flip_visibility Vincent\nend_round\n";
+        let game = Game::run(&mut text.as_bytes(), &rules).unwrap();
+        assert_eq!(game.players["Ron"].energy, Some(8));
+
+        // A partner frozen before the round may be merged, one frozen in the round may not,
+        // and a cancelled super costs nothing
+        let rules = Rules {
+            energy: Energy::parse("regen:0"),
+            ..rounds
+        };
+        let text = "2\nHarry\nDraco\n4\nAnn\n0\n300\nTrue\nBob\n0\n0\nTrue\n\
+                    Eve\n0\n100\nTrue\nCid\n1\n200\nTrue\nattack Cid Eve\n\
+                    super Ann Bob Eve\nend_round\n";
+        let game = Game::run(&mut text.as_bytes(), &rules).unwrap();
+        assert_eq!(game.players["Ann"].energy, Some(10));
+        let text = text.to_string() + "super Ann Bob\nend_round\n";
+        let game = Game::run(&mut text.as_bytes(), &rules).unwrap();
+        let events: Vec<String> = game.events.iter().map(|event| event.to_string()).collect();
+        assert_eq!(
+            events[1..],
+            [
+                "super Ann Bob Eve: conflict: Eve is frozen in the round, the super is cancelled",
+                "super Ann Bob: S_0 300 True energy 10",
+            ]
+        );
+    }
+
+    #[test]
+    fn turn_policies() {
+        let with_policy = |policy| Rules {
+            turn_policy: TurnPolicy::parse(policy).unwrap(),
+            ..Rules::default()
+        };
+        let roster = "2\nHarry\nDraco\n4\nRon\n0\n300\nTrue\nHermione\n0\n100\nFalse\n\
+                      Vincent\n1\n200\nTrue\nGregory\n1\n100\nTrue\n";
+
+        let rotation = roster.to_string()
+            + "attack Ron Gregory\nflip_visibility Hermione\nheal Hermione Ron\n\
+               attack Vincent Ron\nflip_visibility Hermione\n";
+        let game = Game::run(&mut rotation.as_bytes(), &with_policy("rotation")).unwrap();
+        // A player that can not act at all gets the same warning in and out of its turn
+        assert_eq!(
+            game.warnings,
+            [
+                "It is not the turn of this player",
+                "This player can't play"
+            ]
+        );
+        assert!(game.players["Hermione"].is_visible);
+        // Listed moves follow the turn policy too
+        let text = roster.to_string() + "attack Ron Gregory\n";
+        let game = Game::run(&mut text.as_bytes(), &with_policy("rotation")).unwrap();
+        let legal = game.legal_actions();
+        assert!(!legal.is_empty());
+        assert!(legal.iter().all(|action| action.actor() == "Vincent"));
+        assert!(Game::run(&mut rotation.as_bytes(), &Rules::default())
+            .unwrap()
+            .warnings
+            .is_empty());
+
+        let rounds = roster.to_string()
+            + "attack Ron Gregory\nattack Ron Vincent\nend_round\nattack Ron Vincent\n";
+        let game = Game::run(&mut rounds.as_bytes(), &with_policy("player")).unwrap();
+        assert_eq!(game.warnings, ["It is not the turn of this player"]);
+        assert_eq!(game.players["Ron"].power, 800);
+        let game = Game::run(&mut rounds.as_bytes(), &with_policy("budget:2")).unwrap();
+        assert!(game.warnings.is_empty());
+
+        // A round ended by the policy is not ended again by its end_round line
+        let text = "2\nHarry\nDraco\n2\nRon\n0\n300\nTrue\nVincent\n1\n200\nTrue\n\
+                    flip_visibility Ron\nflip_visibility Vincent\nend_round\nflip_visibility Ron\n";
+        let energy = Rules {
+            energy: Energy::parse("flip:8,regen:3,max:10"),
+            ..with_policy("player")
+        };
+        let game = Game::run(&mut text.as_bytes(), &energy).unwrap();
+        assert_eq!(game.warnings, ["This player has insufficient energy"]);
+        assert_eq!(game.players["Ron"].energy, Some(5));
+        let simultaneous = Rules {
+            simultaneous_rounds: true,
+            ..energy
+        };
+        let game = Game::run(&mut text.as_bytes(), &simultaneous).unwrap();
+        assert_eq!(game.warnings, ["This player has insufficient energy"]);
+
+        // Without a policy rounds are not a part of the input
+        assert!(Game::run(&mut rounds.as_bytes(), &Rules::default()).is_err());
+        assert_eq!(TurnPolicy::parse("budget:0"), None);
+    }
+
+    #[test]
+    fn actions_cost_energy() {
+        let energy = Rules {
+            energy: Energy::parse("attack:4,flip:1,regen:2,max:5"),
+            ..Rules::default()
+        };
+        // Listed moves are the ones the players can pay for
+        let text = "1\nHarry\n2\nRon\n0\n300\nTrue\nHermione\n0\n100\nTrue\n";
+        let expensive = Rules {
+            energy: Energy::parse("attack:6,max:5"),
+            ..Rules::default()
+        };
+        let game = Game::run(&mut text.as_bytes(), &expensive).unwrap();
+        assert!(game
+            .legal_actions()
+            .iter()
+            .all(|action| !matches!(action, Action::Attack(_, _))));
+        let text = "2\nHarry\nDraco\n3\nRon\n0\n300\nTrue\nHermione\n0\n100\nTrue\n\
+                    Vincent\n1\n200\nTrue\nattack Ron Vincent\nattack Ron Vincent\n\
+                    heal Ron Vincent\nend_round\nflip_visibility Ron\n";
+        let game = Game::run(&mut text.as_bytes(), &energy).unwrap();
+
+        // Energy is checked after every other warning
+        assert_eq!(
+            game.warnings,
+            [
+                "This player has insufficient energy",
+                "Both players should be from the same team"
+            ]
+        );
+        assert_eq!(
+            game.events[0].to_string(),
+            "attack Ron Vincent: Ron 400 True energy 1, Vincent 0 True energy 5"
+        );
+        assert_eq!(
+            game.view_lines(None, Fog::Off)[2..],
+            [
+                "player Hermione 0 100 True 5",
+                "player Ron 0 400 False 2",
+                "player Vincent 1 0 True 5",
+                "end"
+            ]
+        );
+        assert_eq!(Energy::parse(""), Some(Energy::default()));
+        assert_eq!(Energy::parse("mana:1"), None);
+    }
+
+    #[test]
+    fn timed_effects_expire() {
+        let (rules, _) = Rules::from_args(&[
+            "--invisibility=2:actions".to_string(),
+            "--thaw=1:50".to_string(),
+        ])
+        .unwrap();
+        let text = "2\nHarry\nDraco\n3\nRon\n0\n300\nTrue\nHermione\n0\n100\nTrue\n\
+                    Vincent\n1\n200\nTrue\nflip_visibility Hermione\nattack Ron Vincent\n\
+                    flip_visibility Ron\nend_round\nattack Vincent Hermione\n";
+        let game = Game::run(&mut text.as_bytes(), &rules).unwrap();
+
+        let events: Vec<String> = game.events.iter().map(|event| event.to_string()).collect();
+        assert_eq!(
+            events,
+            [
+                "flip_visibility Hermione: Hermione 100 False",
+                "attack Ron Vincent: Ron 400 True, Vincent 0 True",
+                "flip_visibility Ron: Ron 400 False",
+                "reappear Hermione: Hermione 100 True",
+                "thaw Vincent: Vincent 50 True",
+                "attack Vincent Hermione: Vincent 0 True, Hermione 150 True",
+            ]
+        );
+        assert!(!game.players["Ron"].is_visible);
+        assert_eq!(
+            game.events[3].seen_by(Some(1), Fog::Power).unwrap(),
+            "reappear Hermione: Hermione 100 True"
+        );
+        assert_eq!(Duration::parse("0:rounds"), None);
+
+        // A merged player starts a new timer once it is split
+        let (rules, _) = Rules::from_args(&["--thaw=2:50".to_string()]).unwrap();
+        let text = "2\nHarry\nDraco\n3\nAnn\n0\n300\nTrue\nBob\n0\n0\nTrue\n\
+                    Vincent\n1\n200\nTrue\nsuper Ann Bob\nend_round\nsplit S_0\nend_round\n";
+        let game = Game::run(&mut text.as_bytes(), &rules).unwrap();
+        assert_eq!(game.players["Bob"].power, 0);
+        let text = text.to_string() + "end_round\n";
+        let game = Game::run(&mut text.as_bytes(), &rules).unwrap();
+        assert_eq!(game.players["Bob"].power, 50);
+    }
+
+    #[test]
+    fn tactics() {
+        let text = "2\nHarry\nDraco\n5\nRon\n0\n300\nTrue\nHermione\n0\n200\nTrue\n\
+                    Neville\n0\n0\nTrue\nVincent\n1\n400\nTrue\nGregory\n1\n100\nTrue\n\
+                    shield Ron Hermione\nattack Vincent Ron\nrevive Ron Neville\n\
+                    revive Ron Gregory\nrevive Ron Neville\nrevive Ron Hermione\n\
+                    transfer Ron Neville 50\ntransfer Ron Neville 101\ntransfer Ron Ron 1\n\
+                    shield Ron Ron\nsacrifice Gregory\nsacrifice Gregory\n";
+        let game = Game::run(&mut text.as_bytes(), &Rules::default()).unwrap();
+
+        let events: Vec<String> = game.events.iter().map(|event| event.to_string()).collect();
+        assert_eq!(
+            events[1],
+            "attack Vincent Ron: Vincent 600 True, Hermione 0 True"
+        );
+        assert_eq!(
+            events[2],
+            "revive Ron Neville: Ron 150 True, Neville 100 True"
+        );
+        assert_eq!(
+            events[6],
+            "transfer Ron Neville 50: Ron 100 True, Neville 150 True"
+        );
+        assert_eq!(
+            events[10],
+            "sacrifice Gregory: Gregory 0 True, Vincent 700 True"
+        );
+        assert_eq!(
+            game.warnings,
+            [
+                "Both players should be from the same team",
+                "The player is not frozen",
+                "The player does not have enough power",
+                "The player does not have enough power",
+                "The player cannot transfer power to itself",
+                "The player cannot shield itself",
+                "This player is frozen",
+            ]
+        );
+        assert!(game.shields.is_empty());
+        assert_eq!(game.verdict(), "The chosen wizard is Draco");
+
+        // A merged player is not shielded any more, neither is a player shielded by a merged one
+        let text = "2\nHarry\nDraco\n4\nAnn\n0\n300\nTrue\nBob\n0\n500\nTrue\n\
+                    Cid\n0\n100\nTrue\nDan\n1\n400\nTrue\nshield Ann Bob\nshield Cid Bob\n\
+                    super Ann Bob\n";
+        let game = Game::run(&mut text.as_bytes(), &Rules::default()).unwrap();
+        assert!(game.shields.is_empty());
+        let text = format!("{}attack Dan Ann\n", text);
+        assert!(Game::run(&mut text.as_bytes(), &Rules::default()).is_err());
+
+        // A shield which is not in the game is ignored, the attack is paid only once
+        let energy = Rules {
+            energy: Energy::parse("attack:4,max:10"),
+            ..Rules::default()
+        };
+        let text = "2\nHarry\nDraco\n2\nAnn\n0\n100\nTrue\nDan\n1\n400\nTrue\n";
+        let mut game = Game::run(&mut text.as_bytes(), &energy).unwrap();
+        game.shields.insert("Ann".to_string(), "S_7".to_string());
+        assert_eq!(game.play(&Action::parse("attack Dan Ann").unwrap()), Ok(()));
+        assert_eq!(game.players["Ann"].power, 0);
+        assert_eq!(game.players["Dan"].energy, Some(6));
+
+        assert_eq!(
+            Action::parse("transfer Ron Neville 1000"),
+            Ok(Action::Transfer(
+                "Ron".to_string(),
+                "Neville".to_string(),
+                1000
+            ))
+        );
+        for line in [
+            "transfer Ron Neville",
+            "transfer Ron Neville 0",
+            "transfer Ron Neville 050",
+            "transfer Ron Neville +5",
+            "transfer Ron Neville 1001",
+            "sacrifice Ron Neville",
+            "shield Ron",
+        ] {
+            assert!(Action::parse(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn split_undoes_one_merge() {
+        let text = "2\nHarry\nDraco\n4\nRon\n0\n300\nTrue\nHermione\n0\n100\nTrue\n\
+                    Neville\n0\n200\nTrue\nVincent\n1\n500\nTrue\nsuper Ron Hermione\n\
+                    super S_0 Neville\nattack S_1 Vincent\nsplit S_1\nsplit S_0\n\
+                    split Ron\nsplit Vincent\n";
+        let events = |rules: &Rules| -> Vec<String> {
+            let game = Game::run(&mut text.as_bytes(), rules).unwrap();
+            game.events.iter().map(|event| event.to_string()).collect()
+        };
+
+        let proportional = events(&Rules::default());
+        assert_eq!(
+            proportional[3..],
+            [
+                "split S_1: S_0 467 True, Neville 233 True",
+                "split S_0: Ron 351 True, Hermione 116 True",
+                "split Ron: The player is not a super player",
+                "split Vincent: This player is frozen",
+            ]
+        );
+        let (rules, _) = Rules::from_args(&["--split=equal".to_string()]).unwrap();
+        assert_eq!(
+            events(&rules)[3..5],
+            [
+                "split S_1: S_0 350 True, Neville 350 True",
+                "split S_0: Ron 175 True, Hermione 175 True",
+            ]
+        );
+
+        // Split players are themselves again in the ratings
+        let scenario = Scenario::read(&mut text.as_bytes(), &Rules::default()).unwrap();
+        let (_, contributions) = contributions(&scenario).unwrap();
+        let freezes: Vec<u64> = contributions.iter().map(|player| player.freezes).collect();
+        assert_eq!(freezes, [1, 1, 1, 0]);
+
+        // Energy is shared like the power, it never grows by splitting
+        let energy = Rules {
+            energy: Energy::parse("super:0,split:0,regen:0,max:20"),
+            ..Rules::default()
+        };
+        let text = "2\nHarry\nDraco\n3\nRon\n0\n300\nTrue\nHermione\n0\n100\nTrue\n\
+                    Vincent\n1\n500\nTrue\nsuper Ron Hermione\nsplit S_0\n";
+        let game = Game::run(&mut text.as_bytes(), &energy).unwrap();
+        let total: u64 = ["Ron", "Hermione"]
+            .iter()
+            .map(|name| game.players[*name].energy.unwrap())
+            .sum();
+        assert_eq!(total, 20);
+        assert_eq!(game.players["Ron"].energy, Some(15));
+
+        // Shields made by a split player are gone, the attack reaches its target
+        let text = "2\nHarry\nDraco\n4\nAnn\n0\n300\nTrue\nBob\n0\n100\nTrue\n\
+                    Cid\n0\n200\nTrue\nDan\n1\n500\nTrue\nsuper Ann Bob\nshield Cid S_0\n\
+                    split S_0\nattack Dan Cid\n";
+        let game = Game::run(&mut text.as_bytes(), &Rules::default()).unwrap();
+        assert!(game.shields.is_empty());
+        assert_eq!(
+            game.events.last().unwrap().to_string(),
+            "attack Dan Cid: Dan 800 True, Cid 0 True"
+        );
+        let text = "2\nHarry\nDraco\n4\nAnn\n0\n300\nTrue\nBob\n0\n100\nTrue\n\
+                    Cid\n0\n200\nTrue\nDan\n1\n500\nTrue\nsuper Ann Bob\nend_round\n\
+                    shield Cid S_0\nend_round\nsplit S_0\nend_round\nattack Dan Cid\nend_round\n";
+        let (rules, _) = Rules::from_args(&["--rounds".to_string()]).unwrap();
+        let game = Game::run(&mut text.as_bytes(), &rules).unwrap();
+        assert_eq!(game.players["Cid"].power, 0);
+    }
+
+    #[test]
+    fn super_fuses_many_players() {
+        let text = "2\nHarry\nDraco\n4\nRon\n0\n300\nTrue\nHermione\n0\n100\nTrue\n\
+                    Neville\n0\n700\nFalse\nVincent\n1\n500\nTrue\n\
+                    super Ron Hermione Vincent\nsuper Ron Hermione Ron\n\
+                    super Ron Hermione Hermione\nsuper Ron Hermione Neville\nsplit S_0\n";
+        let game = Game::run(&mut text.as_bytes(), &Rules::default()).unwrap();
+        let events: Vec<String> = game.events.iter().map(|event| event.to_string()).collect();
+        assert_eq!(
+            events,
+            [
+                "super Ron Hermione Vincent: Both players should be from the same team",
+                "super Ron Hermione Ron: The player cannot do super action with itself",
+                "super Ron Hermione Hermione: The player cannot take part in super action twice",
+                "super Ron Hermione Neville: S_0 1000 True",
+                "split S_0: Ron 273 True, Hermione 91 True, Neville 636 True",
+            ]
+        );
+
+        // Every part is merged in simultaneous rounds too
+        let text = text.replace("split S_0\n", "end_round\n");
+        let (rules, _) = Rules::from_args(&["--rounds".to_string()]).unwrap();
+        let game = Game::run(&mut text.as_bytes(), &rules).unwrap();
+        assert_eq!(game.parts("S_0").unwrap(), ["Ron", "Hermione", "Neville"]);
+        assert_eq!(game.players["S_0"].power, 1000);
+    }
 }
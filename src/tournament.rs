@@ -0,0 +1,445 @@
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+};
+
+use crate::{
+    next_string,
+    random::Random,
+    scenario::Scenario,
+    strategy::{play_match, strategy_by_name},
+    Game, Player, INVALID_INPUT,
+};
+
+/// Team entering a tournament: its magician, the bot playing for it and its players
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub magician: String,
+    pub strategy: String,
+    // Team numbers are assigned for every match separately
+    pub players: Vec<Player>,
+}
+
+/// How matches are scheduled
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Format {
+    // Every team plays every other team once
+    RoundRobin,
+    // A team is out after its first loss
+    SingleElimination,
+    // A team is out after its second loss
+    DoubleElimination,
+    // Teams with similar points play each other for log2(teams) rounds
+    Swiss,
+}
+
+/// Settings of a tournament
+#[derive(Clone, Debug)]
+pub struct TournamentConfig {
+    pub format: Format,
+    pub win_points: u64,
+    pub tie_points: u64,
+    // Maximal amount of actions in a match
+    pub max_actions: usize,
+    pub seed: u64,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        Self {
+            format: Format::RoundRobin,
+            win_points: 3,
+            tie_points: 1,
+            max_actions: 100,
+            seed: 0,
+        }
+    }
+}
+
+/// Result of a single match between two teams (indices of entries)
+#[derive(PartialEq, Clone, Debug)]
+pub struct MatchResult {
+    pub round: usize,
+    pub first: usize,
+    pub second: usize,
+    // None for a tie
+    pub winner: Option<usize>,
+}
+
+/// Cumulative results of a team
+#[derive(PartialEq, Clone, Debug)]
+pub struct Standing {
+    pub team: usize,
+    pub points: u64,
+    pub wins: u64,
+    pub ties: u64,
+    pub losses: u64,
+}
+
+/// Everything that happened in a tournament
+#[derive(Clone, Debug)]
+pub struct TournamentResult {
+    pub matches: Vec<MatchResult>,
+    // Sorted from the best team to the worst
+    pub standings: Vec<Standing>,
+    // Team whose magician becomes the club head
+    pub club_head: usize,
+}
+
+/// Reads the tournament file. The format follows the input file:
+/// the amount of teams, then for every team its magician, its strategy,
+/// the amount of its players and 3 lines per player: name, power and visibility
+///
+/// Returns Err(INVALID_INPUT) if the file is malformed, names repeat
+/// or a team has no players
+pub fn read_entries(data_source: &mut impl Read) -> Result<Vec<Entry>, &'static str> {
+    let mut buffer = BufReader::new(data_source).lines();
+    let parse = |line: String| line.parse::<u64>().map_err(|_| INVALID_INPUT);
+
+    let total_teams = parse(next_string(&mut buffer)?)?;
+    if !(2..=100).contains(&total_teams) {
+        return Err(INVALID_INPUT);
+    }
+
+    let mut entries = Vec::new();
+    let mut names = HashSet::new();
+    for _ in 0..total_teams {
+        let magician = Game::validate_name(next_string(&mut buffer)?)?;
+        let strategy = next_string(&mut buffer)?;
+        if strategy_by_name(&strategy, 0).is_none() || !names.insert(magician.clone()) {
+            return Err(INVALID_INPUT);
+        }
+
+        let total_players = parse(next_string(&mut buffer)?)?;
+        if !(1..=100).contains(&total_players) {
+            return Err(INVALID_INPUT);
+        }
+        let mut players = Vec::new();
+        for _ in 0..total_players {
+            let name = Game::validate_name(next_string(&mut buffer)?)?;
+            let power = Game::validate_power(parse(next_string(&mut buffer)?)?)?;
+            let is_visible = Game::validate_visibility(next_string(&mut buffer)?)?;
+            if !names.insert(name.clone()) {
+                return Err(INVALID_INPUT);
+            }
+            players.push(Player {
+                name,
+                team_number: 0,
+                power,
+                is_visible,
+                energy: None,
+            });
+        }
+
+        entries.push(Entry {
+            magician,
+            strategy,
+            players,
+        });
+    }
+    Ok(entries)
+}
+
+/// Runs matches and keeps the standings
+struct Tournament<'a> {
+    entries: &'a [Entry],
+    config: &'a TournamentConfig,
+    matches: Vec<MatchResult>,
+    standings: Vec<Standing>,
+}
+
+impl<'a> Tournament<'a> {
+    fn new(entries: &'a [Entry], config: &'a TournamentConfig) -> Self {
+        Self {
+            entries,
+            config,
+            matches: Vec::new(),
+            standings: (0..entries.len())
+                .map(|team| Standing {
+                    team,
+                    points: 0,
+                    wins: 0,
+                    ties: 0,
+                    losses: 0,
+                })
+                .collect(),
+        }
+    }
+
+    /// Plays a match between two entries with their bots and records the result
+    fn play(&mut self, round: usize, first: usize, second: usize) -> Option<usize> {
+        let mut players = Vec::new();
+        for (team_number, team) in [first, second].into_iter().enumerate() {
+            for player in &self.entries[team].players {
+                players.push(Player {
+                    team_number: team_number as u64,
+                    ..player.clone()
+                });
+            }
+        }
+        let roster = Scenario {
+            teams: vec![
+                self.entries[first].magician.clone(),
+                self.entries[second].magician.clone(),
+            ],
+            players,
+            actions: Vec::new(),
+        };
+
+        // Every match has its own random numbers, so results do not depend on the format
+        let mut random = Random::stream(self.config.seed, self.matches.len() as u64);
+        let mut strategies = [first, second]
+            .iter()
+            .map(|&team| strategy_by_name(&self.entries[team].strategy, random.next_u64()).unwrap())
+            .collect::<Vec<_>>();
+        let log = play_match(&roster, &mut strategies, self.config.max_actions);
+        let winner = log
+            .play()
+            .unwrap()
+            .get_winner_index()
+            .map(|index| [first, second][index]);
+
+        self.record(first, second, winner);
+        self.matches.push(MatchResult {
+            round,
+            first,
+            second,
+            winner,
+        });
+        winner
+    }
+
+    /// Adds the result of a match to the standings
+    fn record(&mut self, first: usize, second: usize, winner: Option<usize>) {
+        match winner {
+            Some(winner) => {
+                let loser = if winner == first { second } else { first };
+                self.standings[winner].points += self.config.win_points;
+                self.standings[winner].wins += 1;
+                self.standings[loser].losses += 1;
+            }
+            None => {
+                for team in [first, second] {
+                    self.standings[team].points += self.config.tie_points;
+                    self.standings[team].ties += 1;
+                }
+            }
+        }
+    }
+
+    /// Plays an elimination match. Ties are replayed a few times,
+    /// after that the team earlier in the file goes through
+    fn play_decisive(&mut self, round: usize, first: usize, second: usize) -> (usize, usize) {
+        for _ in 0..3 {
+            if let Some(winner) = self.play(round, first, second) {
+                let loser = if winner == first { second } else { first };
+                return (winner, loser);
+            }
+        }
+        (first.min(second), first.max(second))
+    }
+
+    /// Teams from the best to the worst: by points, then wins, then order in the file
+    fn ranking(&self) -> Vec<Standing> {
+        let mut ranking = self.standings.clone();
+        ranking.sort_by_key(|standing| {
+            (
+                Reverse(standing.points),
+                Reverse(standing.wins),
+                standing.team,
+            )
+        });
+        ranking
+    }
+
+    fn round_robin(&mut self) -> usize {
+        let teams = self.entries.len();
+        let mut round = 0;
+        for first in 0..teams {
+            for second in first + 1..teams {
+                round += 1;
+                self.play(round, first, second);
+            }
+        }
+        self.ranking()[0].team
+    }
+
+    fn single_elimination(&mut self) -> usize {
+        let mut alive: Vec<usize> = (0..self.entries.len()).collect();
+        let mut round = 0;
+        while alive.len() > 1 {
+            round += 1;
+            // The best seeds play the worst ones, the best seed gets a bye if needed
+            let mut next = Vec::new();
+            if alive.len() % 2 == 1 {
+                next.push(alive.remove(0));
+            }
+            let half = alive.len() / 2;
+            for index in 0..half {
+                let (winner, _) =
+                    self.play_decisive(round, alive[index], alive[alive.len() - 1 - index]);
+                next.push(winner);
+            }
+            next.sort();
+            alive = next;
+        }
+        alive[0]
+    }
+
+    fn double_elimination(&mut self) -> usize {
+        let mut losses = vec![0; self.entries.len()];
+        let mut round = 0;
+        loop {
+            let bracket = |count| {
+                (0..losses.len())
+                    .filter(|&team| losses[team] == count)
+                    .collect::<Vec<usize>>()
+            };
+            let (winners, losers) = (bracket(0), bracket(1));
+            if winners.len() + losers.len() == 1 {
+                return winners.into_iter().chain(losers).next().unwrap();
+            }
+
+            round += 1;
+            let pairs: Vec<(usize, usize)> = if winners.len() == 1 && losers.len() == 1 {
+                // Grand final, the undefeated team has to lose twice
+                vec![(winners[0], losers[0])]
+            } else {
+                // Teams play inside their bracket, odd teams get a bye
+                [winners, losers]
+                    .iter()
+                    .flat_map(|bracket| {
+                        (0..bracket.len() / 2)
+                            .map(|index| (bracket[index], bracket[bracket.len() - 1 - index]))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect()
+            };
+            for (first, second) in pairs {
+                let (_, loser) = self.play_decisive(round, first, second);
+                losses[loser] += 1;
+            }
+        }
+    }
+
+    fn swiss(&mut self) -> usize {
+        let teams = self.entries.len();
+        let rounds = teams.next_power_of_two().trailing_zeros() as usize;
+        let mut played = HashSet::new();
+        let mut had_bye = HashSet::new();
+
+        for round in 1..=rounds {
+            let mut waiting: Vec<usize> = self
+                .ranking()
+                .iter()
+                .map(|standing| standing.team)
+                .collect();
+
+            // The lowest ranked team without a bye sits out and gets a win
+            if waiting.len() % 2 == 1 {
+                let index = (0..waiting.len())
+                    .rev()
+                    .find(|index| !had_bye.contains(&waiting[*index]))
+                    .unwrap_or(waiting.len() - 1);
+                let team = waiting.remove(index);
+                had_bye.insert(team);
+                self.standings[team].points += self.config.win_points;
+                self.standings[team].wins += 1;
+            }
+
+            // Pair every team with the next team it has not played yet, if possible
+            while !waiting.is_empty() {
+                let first = waiting.remove(0);
+                let index = (0..waiting.len())
+                    .find(|index| {
+                        !played.contains(&(first.min(waiting[*index]), first.max(waiting[*index])))
+                    })
+                    .unwrap_or(0);
+                let second = waiting.remove(index);
+                played.insert((first.min(second), first.max(second)));
+                self.play(round, first, second);
+            }
+        }
+        self.ranking()[0].team
+    }
+}
+
+/// Plays a tournament between the entries. At least two entries are expected
+pub fn run_tournament(entries: &[Entry], config: &TournamentConfig) -> TournamentResult {
+    let mut tournament = Tournament::new(entries, config);
+    let club_head = match config.format {
+        Format::RoundRobin => tournament.round_robin(),
+        Format::SingleElimination => tournament.single_elimination(),
+        Format::DoubleElimination => tournament.double_elimination(),
+        Format::Swiss => tournament.swiss(),
+    };
+
+    TournamentResult {
+        standings: tournament.ranking(),
+        matches: tournament.matches,
+        club_head,
+    }
+}
+
+/// `tournament <file> [--format round-robin|single|double|swiss] [--win P] [--tie P]
+/// [--actions K] [--seed S]` command
+pub fn command(args: &[String]) -> Result<(), String> {
+    let usage = "Usage: tournament <file> [--format round-robin|single|double|swiss] \
+                 [--win P] [--tie P] [--actions K] [--seed S]";
+    let path = args.first().ok_or(usage)?;
+
+    let mut config = TournamentConfig::default();
+    let mut flags = args[1..].iter();
+    while let Some(flag) = flags.next() {
+        let value = flags.next().ok_or(usage)?;
+        let invalid = || format!("Invalid value `{}` of `{}`", value, flag);
+        match flag.as_str() {
+            "--format" => {
+                config.format = match value.as_str() {
+                    "round-robin" => Format::RoundRobin,
+                    "single" => Format::SingleElimination,
+                    "double" => Format::DoubleElimination,
+                    "swiss" => Format::Swiss,
+                    _ => return Err(invalid()),
+                }
+            }
+            "--win" => config.win_points = value.parse().map_err(|_| invalid())?,
+            "--tie" => config.tie_points = value.parse().map_err(|_| invalid())?,
+            "--actions" => config.max_actions = value.parse().map_err(|_| invalid())?,
+            "--seed" => config.seed = value.parse().map_err(|_| invalid())?,
+            _ => return Err(usage.to_string()),
+        }
+    }
+
+    let mut input = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let entries = read_entries(&mut input).map_err(|e| format!("{}: {}", path, e))?;
+    let result = run_tournament(&entries, &config);
+
+    let name = |team: usize| &entries[team].magician;
+    for game in &result.matches {
+        println!(
+            "Round {}: {} vs {}: {}",
+            game.round,
+            name(game.first),
+            name(game.second),
+            game.winner.map_or("tie", |winner| name(winner).as_str())
+        );
+    }
+    println!();
+    for (place, standing) in result.standings.iter().enumerate() {
+        println!(
+            "{}. {} {} points ({} wins, {} ties, {} losses)",
+            place + 1,
+            name(standing.team),
+            standing.points,
+            standing.wins,
+            standing.ties,
+            standing.losses
+        );
+    }
+    println!();
+    println!("The club head is {}", name(result.club_head));
+    Ok(())
+}
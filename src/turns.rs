@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+
+use crate::{Action, Game};
+
+/// Which actions teams may take, see `Rules::turn_policy`
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum TurnPolicy {
+    // Any player acts at any time, as the task says
+    #[default]
+    Free,
+    // Teams act one after another in the order of their numbers,
+    // teams without unfrozen players are skipped
+    Rotation,
+    // Every player acts at most once per round
+    OnePerPlayer,
+    // Every team takes at most the given amount of actions per round
+    Budget(usize),
+}
+
+impl TurnPolicy {
+    /// Parses `free`, `rotation`, `player` or `budget:N`
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "free" => Some(TurnPolicy::Free),
+            "rotation" => Some(TurnPolicy::Rotation),
+            "player" => Some(TurnPolicy::OnePerPlayer),
+            _ => match text.strip_prefix("budget:")?.parse() {
+                Ok(budget) if budget > 0 => Some(TurnPolicy::Budget(budget)),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Actions already taken under the turn policy of the game
+#[derive(Clone, Default, Debug)]
+pub struct Turns {
+    pub policy: TurnPolicy,
+    // Team of the last taken action, for the rotation
+    last_team: Option<usize>,
+    // Players which acted in the current round
+    acted: HashSet<String>,
+    // Actions taken by every team in the current round
+    spent: Vec<usize>,
+    // The policy ended the round and nothing was taken since
+    ended: bool,
+}
+
+impl Game {
+    /// Checks whether the turn policy does not allow the actor of the action to act now,
+    /// see `warning_of` for the order of the warnings. The actor has to exist
+    pub fn out_of_turn(&self, action: &Action) -> bool {
+        let actor = &self.players[action.actor()];
+        let team = actor.team_number as usize;
+        let allowed = match self.turns.policy {
+            TurnPolicy::Free => true,
+            TurnPolicy::Rotation => self.team_in_turn().is_none_or(|next| next == team),
+            TurnPolicy::OnePerPlayer => !self.turns.acted.contains(&actor.name),
+            TurnPolicy::Budget(budget) => self
+                .turns
+                .spent
+                .get(team)
+                .is_none_or(|&spent| spent < budget),
+        };
+        !allowed
+    }
+
+    /// Counts a performed action of the actor from the team. The round ends by itself
+    /// once nobody may act in it any more
+    pub fn take_turn(&mut self, team: u64, actor: &str) {
+        if self.turns.policy == TurnPolicy::Free {
+            return;
+        }
+        self.count_turn(team, actor);
+
+        let team = team as usize;
+        let over = match self.turns.policy {
+            TurnPolicy::Free => false,
+            // The round ends once every team had its turn
+            TurnPolicy::Rotation => self.team_in_turn().is_none_or(|next| next <= team),
+            TurnPolicy::OnePerPlayer => self
+                .players
+                .values()
+                .all(|player| player.power == 0 || self.turns.acted.contains(&player.name)),
+            TurnPolicy::Budget(budget) => self.players.values().all(|player| {
+                player.power == 0 || self.turns.spent[player.team_number as usize] >= budget
+            }),
+        };
+        if over {
+            self.end_round();
+            self.turns.ended = true;
+        }
+    }
+
+    /// Counts a performed action of the actor from the team, the round goes on
+    pub fn count_turn(&mut self, team: u64, actor: &str) {
+        let team = team as usize;
+        self.turns.last_team = Some(team);
+        self.turns.acted.insert(actor.to_string());
+        if self.turns.spent.len() < self.teams.len() {
+            self.turns.spent.resize(self.teams.len(), 0);
+        }
+        self.turns.spent[team] += 1;
+        self.turns.ended = false;
+    }
+
+    /// Ends the round at an `end_round` line, unless the turn policy has just ended it,
+    /// so every round ends exactly once
+    pub fn close_round(&mut self) {
+        if !std::mem::take(&mut self.turns.ended) {
+            self.end_round();
+        }
+    }
+
+    /// Starts a new round: every player and team may act again, players regenerate
+    /// energy and effects lasting rounds may expire. The rotation goes on from
+    /// the team it stopped at
+    pub fn end_round(&mut self) {
+        self.turns.acted.clear();
+        self.turns.spent.clear();
+        self.regenerate_energy();
+        self.tick_round();
+    }
+
+    /// Team which acts next in the rotation, None if no team has unfrozen players
+    fn team_in_turn(&self) -> Option<usize> {
+        let first = self.turns.last_team.map_or(0, |team| team + 1);
+        (first..first + self.teams.len())
+            .map(|team| team % self.teams.len())
+            .find(|&team| {
+                self.players
+                    .values()
+                    .any(|player| player.team_number as usize == team && player.power > 0)
+            })
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{
+    event::{describe, Event, Outcome},
+    Action, Game, Player,
+};
+
+/// What a team does not see of invisible enemies
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Fog {
+    // Everything is seen
+    Off,
+    // Invisible enemies are seen without their power
+    Power,
+    // Invisible enemies are not seen at all
+    Existence,
+}
+
+impl Fog {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Fog::Off),
+            "power" => Some(Fog::Power),
+            "existence" => Some(Fog::Existence),
+            _ => None,
+        }
+    }
+
+    /// Checks whether the player is hidden from the team.
+    /// A spectator (no team) has no teammates, so it does not see any invisible player
+    fn hides(&self, player: &Player, team: Option<usize>) -> bool {
+        *self != Fog::Off && !player.is_visible && team != Some(player.team_number as usize)
+    }
+}
+
+/// A player as some team sees it
+#[derive(PartialEq, Clone, Debug)]
+pub struct PlayerView {
+    pub name: String,
+    pub team_number: u64,
+    // None if the power is hidden
+    pub power: Option<u64>,
+    pub is_visible: bool,
+    // None if the energy is hidden or actions cost nothing
+    pub energy: Option<u64>,
+}
+
+impl Game {
+    /// Players as the team sees them, sorted by name. The own team is always seen fully
+    pub fn view(&self, team: Option<usize>, fog: Fog) -> Vec<PlayerView> {
+        let mut names: Vec<&String> = self.players.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .map(|name| &self.players[name])
+            .filter(|player| !(fog == Fog::Existence && fog.hides(player, team)))
+            .map(|player| {
+                let seen = !fog.hides(player, team);
+                PlayerView {
+                    name: player.name.clone(),
+                    team_number: player.team_number,
+                    power: seen.then_some(player.power),
+                    is_visible: player.is_visible,
+                    energy: player.energy.filter(|_| seen),
+                }
+            })
+            .collect()
+    }
+
+    /// Players the event of the action is about, as they are before it: the players
+    /// it names, the acting player first, then the other players it changes
+    pub fn involved(&self, action: &Action) -> Vec<Player> {
+        let mut names: Vec<String> = action
+            .players()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        for name in self.affected(action) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+            .iter()
+            .filter_map(|name| self.players.get(name).cloned())
+            .collect()
+    }
+
+    /// State as the team sees it: `team <magician> <power>` per team,
+    /// `player <name> <team> <power> <visibility>` per seen player and `end`.
+    /// A hidden power is written as `?`, so is the power of a team with such players,
+    /// seen or not. If actions cost energy, player lines end with the energy,
+    /// hidden the same way
+    pub fn view_lines(&self, team: Option<usize>, fog: Fog) -> Vec<String> {
+        let players = self.view(team, fog);
+        let mut powers = vec![Some(0); self.teams.len()];
+        for player in &players {
+            let total = &mut powers[player.team_number as usize];
+            *total = total.zip(player.power).map(|(total, power)| total + power);
+        }
+        // Players which are not seen at all still count in the power of their team
+        for player in self
+            .players
+            .values()
+            .filter(|player| fog.hides(player, team))
+        {
+            powers[player.team_number as usize] = None;
+        }
+        let unknown = |power: Option<u64>| power.map_or("?".to_string(), |power| power.to_string());
+
+        self.teams
+            .iter()
+            .zip(powers)
+            .map(|(magician, power)| format!("team {} {}", magician, unknown(power)))
+            .chain(players.iter().map(|player| {
+                let visibility = if player.is_visible { "True" } else { "False" };
+                let line = format!(
+                    "player {} {} {} {}",
+                    player.name,
+                    player.team_number,
+                    unknown(player.power),
+                    visibility
+                );
+                match self.energy {
+                    Some(_) => format!("{} {}", line, unknown(player.energy)),
+                    None => line,
+                }
+            }))
+            .chain(["end".to_string()])
+            .collect()
+    }
+}
+
+impl Event {
+    /// The event as the team sees it. Hidden players of the outcome are only named,
+    /// an attack on a hidden target or shield only says that the target was not visible.
+    /// With `Fog::Existence` hidden players are never named: the team sees only its own
+    /// actions on them, and nothing else about them
+    ///
+    /// Returns None if the team does not see the event
+    pub fn seen_by(&self, team: Option<usize>, fog: Fog) -> Option<String> {
+        let outcome: Vec<&Player> = match &self.outcome {
+            Outcome::Changed(players) => players.iter().collect(),
+            Outcome::Expired(_, player) => vec![player],
+            Outcome::Warning(_) | Outcome::Conflict(_) => Vec::new(),
+        };
+        let hidden = self
+            .involved
+            .iter()
+            .chain(outcome)
+            .any(|player| fog.hides(player, team));
+        let own = self
+            .involved
+            .first()
+            .is_some_and(|actor| team == Some(actor.team_number as usize));
+        if !hidden {
+            return Some(self.to_string());
+        }
+
+        match (&self.action, &self.outcome) {
+            (_, Outcome::Expired(effect, player)) if fog != Fog::Existence => Some(format!(
+                "{} {}: {} hidden",
+                effect, player.name, player.name
+            )),
+            (Some(action), Outcome::Changed(players)) if own || fog != Fog::Existence => {
+                let players: Vec<String> = players
+                    .iter()
+                    .map(|player| match fog.hides(player, team) {
+                        false => describe(player),
+                        true if matches!(action, Action::Attack(_, _)) => {
+                            "the target was not visible".to_string()
+                        }
+                        true => format!("{} hidden", player.name),
+                    })
+                    .collect();
+                Some(format!("{}: {}", action, players.join(", ")))
+            }
+            // Warnings are about the acting player, which the team sees if it is its own
+            (_, Outcome::Warning(_)) if own || fog != Fog::Existence => Some(self.to_string()),
+            (_, Outcome::Conflict(_)) if fog != Fog::Existence => Some(self.to_string()),
+            _ => None,
+        }
+    }
+}